@@ -0,0 +1,159 @@
+use crate::id::Id;
+use crate::storage::StorageKey;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, BorshStorageKey};
+
+/// Bytes of storage used by the record of a deployment besides the Id of
+/// the release.
+///
+/// It covers the first record of a target and of a release with account Ids
+/// of the maximal length. The Id is stored twice.
+pub const RECORD_OVERHEAD: u64 = 1024;
+
+/// Outcome of a deployment.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
+pub enum Outcome {
+    /// The upgrade method of the target called by `deploy_to` succeeded.
+    Succeeded,
+    /// The upgrade method of the target called by `deploy_to` failed.
+    Failed,
+    /// The deployment was made elsewhere and recorded by the deployer.
+    Recorded,
+}
+
+/// A deployment of a release to a target account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct Deployment {
+    pub target: AccountId,
+    pub id: Id,
+    /// Height of the block the deployment was recorded in.
+    pub block_height: U64,
+    pub deployer: AccountId,
+    pub outcome: Outcome,
+}
+
+/// Returns the number of bytes of storage which the record of a deployment
+/// of the release may use at most.
+#[must_use]
+pub fn record_size(id: &Id) -> u64 {
+    let id_len = id.try_to_vec().map_or(0, |id| id.len() as u64);
+    RECORD_OVERHEAD + 2 * id_len
+}
+
+/// Prefix of the history of deployments of a target account.
+#[derive(BorshSerialize, BorshStorageKey)]
+struct HistoryKey {
+    /// Always [`StorageKey::DeploymentHistory`].
+    prefix: StorageKey,
+    target: AccountId,
+}
+
+/// Wrapper over NEAR `LookupMap` to keep the history of deployments of each
+/// target account.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DeploymentStorage {
+    /// Target accounts mapped to their deployments, the most recent one last.
+    targets: LookupMap<AccountId, Vector<Deployment>>,
+    /// Releases mapped to the accounts they were deployed to.
+    releases: LookupMap<Id, Vec<AccountId>>,
+}
+
+impl DeploymentStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, deployment: &Deployment) {
+        let target = deployment.target.clone();
+        let mut targets = self.releases.get(&deployment.id).unwrap_or_default();
+        if !targets.contains(&target) {
+            targets.push(target.clone());
+            self.releases.insert(&deployment.id, &targets);
+        }
+        let mut history = self
+            .targets
+            .get(&target)
+            .unwrap_or_else(|| Vector::new(history_key(&target)));
+        history.push(deployment);
+        self.targets.insert(&target, &history);
+    }
+
+    /// Returns the deployments of the release to all accounts, in the order
+    /// they were made for each account.
+    #[must_use]
+    pub fn of_release(&self, id: &Id) -> Vec<Deployment> {
+        self.releases
+            .get(id)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|target| self.targets.get(target))
+            .flat_map(|history| history.to_vec())
+            .filter(|deployment| &deployment.id == id)
+            .collect()
+    }
+
+    /// Returns the most recent deployment to the account which didn't fail.
+    #[must_use]
+    pub fn current(&self, target: &AccountId) -> Option<Deployment> {
+        self.targets
+            .get(target)?
+            .iter()
+            .rev()
+            .find(|deployment| deployment.outcome != Outcome::Failed)
+    }
+}
+
+fn history_key(target: &AccountId) -> HistoryKey {
+    HistoryKey {
+        prefix: StorageKey::DeploymentHistory,
+        target: target.clone(),
+    }
+}
+
+impl Default for DeploymentStorage {
+    fn default() -> Self {
+        Self {
+            targets: LookupMap::new(StorageKey::DeploymentTargets),
+            releases: LookupMap::new(StorageKey::DeploymentReleases),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::{Checksum, Version};
+
+    fn deployment(target: &str, version: &str, outcome: Outcome) -> Deployment {
+        Deployment {
+            target: target.parse().unwrap(),
+            id: Id::new(Version::try_from(version).unwrap(), Checksum(vec![1; 32])),
+            block_height: 1.into(),
+            deployer: "deployer.near".parse().unwrap(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_current_skips_failed_deployments() {
+        let mut storage = DeploymentStorage::new();
+        let old = deployment("aurora", "v1.0.0", Outcome::Succeeded);
+        let new = deployment("aurora", "v1.1.0", Outcome::Failed);
+        let other = deployment("testnet.aurora", "v1.0.0", Outcome::Recorded);
+        storage.record(&old);
+        storage.record(&new);
+        storage.record(&other);
+
+        let target = "aurora".parse().unwrap();
+        assert_eq!(storage.current(&target), Some(old.clone()));
+        assert_eq!(storage.of_release(&old.id), vec![old, other]);
+        assert_eq!(storage.of_release(&new.id), vec![new]);
+        assert_eq!(storage.current(&"unknown".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::id::Id;
+use crate::storage::StorageKey;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::env;
+use near_sdk::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A 20-byte Ethereum address, `0x` prefixed hex encoded in JSON.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EthAddress(pub [u8; 20]);
+
+/// A 65-byte secp256k1 signature made of `r`, `s` and `v`, `0x` prefixed hex
+/// encoded in JSON. `v` is either 27 or 28, or the recovery id 0 or 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EthSignature(pub [u8; 65]);
+
+/// A signature over a release along with the address recovered from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct Attestation {
+    pub address: EthAddress,
+    pub signature: EthSignature,
+}
+
+impl fmt::Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl TryFrom<&str> for EthAddress {
+    type Error = error::EthError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        decode_hex(value)
+            .map(Self)
+            .ok_or_else(|| error::EthError::InvalidAddress(value.to_string()))
+    }
+}
+
+impl TryFrom<&str> for EthSignature {
+    type Error = error::EthError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        decode_hex(value)
+            .map(Self)
+            .ok_or(error::EthError::InvalidSignature)
+    }
+}
+
+fn decode_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(value).ok()?.try_into().ok()
+}
+
+impl Serialize for EthAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EthAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as Deserialize>::deserialize(deserializer)?;
+        Self::try_from(value.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for EthSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.0)))
+    }
+}
+
+impl<'de> Deserialize<'de> for EthSignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as Deserialize>::deserialize(deserializer)?;
+        Self::try_from(value.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl EthSignature {
+    /// Recovers the address which signed the message following EIP-191,
+    /// i.e. `personal_sign` of Ethereum wallets.
+    #[must_use]
+    pub fn recover(&self, message: &[u8]) -> Option<EthAddress> {
+        let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        prefixed.extend_from_slice(message);
+        let hash = env::keccak256(&prefixed);
+        let v = match self.0[64] {
+            v @ (0 | 1) => v,
+            v @ (27 | 28) => v - 27,
+            _ => return None,
+        };
+        let public_key = env::ecrecover(&hash, &self.0[..64], v, true)?;
+        let hash = env::keccak256(&public_key);
+        hash[12..].try_into().ok().map(EthAddress)
+    }
+}
+
+/// Wrapper over NEAR `UnorderedSet` to keep the registered Ethereum signers
+/// and their attestations of releases.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct EthSignerStorage {
+    signers: UnorderedSet<EthAddress>,
+    attestations: LookupMap<Id, Vec<Attestation>>,
+}
+
+impl EthSignerStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the signer. Returns `false` if it was already registered.
+    pub fn add_signer(&mut self, address: &EthAddress) -> bool {
+        self.signers.insert(address)
+    }
+
+    /// Unregisters the signer. Its attestations are kept, but aren't
+    /// reported anymore. Returns `false` if it wasn't registered.
+    pub fn remove_signer(&mut self, address: &EthAddress) -> bool {
+        self.signers.remove(address)
+    }
+
+    #[must_use]
+    pub fn signers(&self) -> Vec<EthAddress> {
+        self.signers.to_vec()
+    }
+
+    /// Recovers the signers of the release Id. Repeated signatures of the
+    /// same signer are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a signature is invalid or if a recovered signer
+    /// isn't registered.
+    pub fn verify(
+        &self,
+        id: &Id,
+        signatures: &[EthSignature],
+    ) -> Result<Vec<Attestation>, error::EthError> {
+        let message = id.to_string();
+        let mut attestations: Vec<Attestation> = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            let Some(address) = signature.recover(message.as_bytes()) else {
+                return Err(error::EthError::InvalidSignature);
+            };
+            if !self.signers.contains(&address) {
+                return Err(error::EthError::UnknownSigner(address.to_string()));
+            }
+            if !attestations
+                .iter()
+                .any(|attestation| attestation.address == address)
+            {
+                attestations.push(Attestation {
+                    address,
+                    signature: *signature,
+                });
+            }
+        }
+        Ok(attestations)
+    }
+
+    /// Stores the verified attestations of the release along with the ones
+    /// stored before.
+    pub fn add_attestations(&mut self, id: &Id, attestations: Vec<Attestation>) {
+        if attestations.is_empty() {
+            return;
+        }
+        let mut stored = self.attestations.get(id).unwrap_or_default();
+        for attestation in attestations {
+            if !stored
+                .iter()
+                .any(|other| other.address == attestation.address)
+            {
+                stored.push(attestation);
+            }
+        }
+        self.attestations.insert(id, &stored);
+    }
+
+    /// Returns the registered signers which attested to the release.
+    #[must_use]
+    pub fn attested_by(&self, id: &Id) -> Vec<EthAddress> {
+        self.attestations
+            .get(id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|attestation| attestation.address)
+            .filter(|address| self.signers.contains(address))
+            .collect()
+    }
+}
+
+impl Default for EthSignerStorage {
+    fn default() -> Self {
+        Self {
+            signers: UnorderedSet::new(StorageKey::EthSigners),
+            attestations: LookupMap::new(StorageKey::EthAttestations),
+        }
+    }
+}
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum EthError {
+        #[error("invalid Ethereum address `{0}`")]
+        InvalidAddress(String),
+        #[error("invalid secp256k1 signature")]
+        InvalidSignature,
+        #[error("Ethereum signer {0} is not registered")]
+        UnknownSigner(String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::{Checksum, Version};
+    use near_crypto::{KeyType, SecretKey};
+
+    /// Signs the message like `personal_sign`, returning the signer address
+    /// and the signature with `v` of 27 or 28.
+    fn personal_sign(seed: &str, message: &[u8]) -> (EthAddress, EthSignature) {
+        let secret_key = SecretKey::from_seed(KeyType::SECP256K1, seed);
+        let public_key = secret_key.public_key();
+        let hash = env::keccak256(public_key.key_data());
+        let address = EthAddress(hash[12..].try_into().unwrap());
+
+        let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        prefixed.extend_from_slice(message);
+        let signature = secret_key.sign(&env::keccak256(&prefixed));
+        let signature = signature.to_string();
+        let encoded = signature.strip_prefix("secp256k1:").unwrap();
+        let mut bytes: [u8; 65] = near_sdk::bs58::decode(encoded)
+            .into_vec()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        bytes[64] += 27;
+        (address, EthSignature(bytes))
+    }
+
+    #[test]
+    fn test_address_json() {
+        let json = "\"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\"";
+        let address: EthAddress = near_sdk::serde_json::from_str(json).unwrap();
+        assert_eq!(near_sdk::serde_json::to_string(&address).unwrap(), json);
+        assert!(EthAddress::try_from("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea").is_err());
+    }
+
+    #[test]
+    fn test_attest() {
+        let id = Id::new(Version::try_from("v1.0.0").unwrap(), Checksum(vec![1; 32]));
+        let message = id.to_string();
+        let (alice, alice_signature) = personal_sign("alice", message.as_bytes());
+        let (bob, bob_signature) = personal_sign("bob", message.as_bytes());
+        assert_eq!(alice_signature.recover(message.as_bytes()), Some(alice));
+
+        let mut storage = EthSignerStorage::new();
+        storage.add_signer(&alice);
+        assert!(matches!(
+            storage.verify(&id, &[bob_signature]),
+            Err(error::EthError::UnknownSigner(_))
+        ));
+        let (_, other_signature) = personal_sign("alice", b"v1.0.1");
+        assert!(matches!(
+            storage.verify(&id, &[other_signature]),
+            Err(error::EthError::UnknownSigner(_))
+        ));
+
+        storage.add_signer(&bob);
+        let attestations = storage
+            .verify(&id, &[alice_signature, bob_signature, alice_signature])
+            .unwrap();
+        assert_eq!(attestations.len(), 2);
+        assert_eq!(storage.attested_by(&id), vec![]);
+        storage.add_attestations(&id, attestations);
+        assert_eq!(storage.attested_by(&id), vec![alice, bob]);
+
+        //== Attestations of unregistered signers aren't reported
+        storage.remove_signer(&bob);
+        assert_eq!(storage.attested_by(&id), vec![alice]);
+    }
+}
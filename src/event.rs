@@ -0,0 +1,440 @@
+use crate::metadata::ReleaseMetadata;
+use crate::roles::Role;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, AccountId};
+
+/// The standard name of the events, following NEP-297.
+pub const STANDARD: &str = "aurora-release-repository";
+/// The version of the events standard.
+pub const VERSION: &str = "1.0.0";
+
+/// An event logged on a change of the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    ReleasePushed(Vec<ReleasePushed>),
+    ReleaseYanked(Vec<ReleaseYanked>),
+    ReleaseRestored(Vec<ReleaseRestored>),
+    LatestChanged(Vec<LatestChanged>),
+    OwnerChanged(Vec<OwnerChanged>),
+    ReleaseDeployed(Vec<ReleaseDeployed>),
+    ReleasePurged(Vec<ReleasePurged>),
+    ReleasePromoted(Vec<ReleasePromoted>),
+    RoleGranted(Vec<RoleChanged>),
+    RoleRevoked(Vec<RoleChanged>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleasePushed {
+    pub id: String,
+    /// Size of the release data in bytes.
+    pub size: u64,
+    pub actor: AccountId,
+    pub metadata: ReleaseMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseYanked {
+    pub id: String,
+    pub actor: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseRestored {
+    pub id: String,
+    pub actor: AccountId,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatestChanged {
+    /// Omitted for the default package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// The new latest release, or `null` if there is none.
+    pub id: Option<String>,
+    pub actor: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnerChanged {
+    pub old_owner: AccountId,
+    pub new_owner: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseDeployed {
+    pub id: String,
+    pub target: AccountId,
+    pub method: String,
+    pub actor: AccountId,
+    /// Whether the call to the target succeeded.
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleasePurged {
+    pub id: String,
+    /// Size of the purged data in bytes.
+    pub size: u64,
+    pub actor: AccountId,
+}
+
+/// The release became the latest one of the channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleasePromoted {
+    pub id: String,
+    pub channel: String,
+    pub actor: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoleChanged {
+    pub account_id: AccountId,
+    pub role: Role,
+    pub actor: AccountId,
+}
+
+#[derive(Serialize)]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+impl Event {
+    #[must_use]
+    pub fn release_pushed(
+        id: String,
+        size: u64,
+        actor: AccountId,
+        metadata: ReleaseMetadata,
+    ) -> Self {
+        Self::ReleasePushed(vec![ReleasePushed {
+            id,
+            size,
+            actor,
+            metadata,
+        }])
+    }
+
+    #[must_use]
+    pub fn release_yanked(id: String, actor: AccountId) -> Self {
+        Self::ReleaseYanked(vec![ReleaseYanked { id, actor }])
+    }
+
+    #[must_use]
+    pub fn release_restored(id: String, actor: AccountId, reason: Option<String>) -> Self {
+        Self::ReleaseRestored(vec![ReleaseRestored { id, actor, reason }])
+    }
+
+    #[must_use]
+    pub fn latest_changed(package: Option<String>, id: Option<String>, actor: AccountId) -> Self {
+        Self::LatestChanged(vec![LatestChanged { package, id, actor }])
+    }
+
+    #[must_use]
+    pub fn owner_changed(old_owner: AccountId, new_owner: AccountId) -> Self {
+        Self::OwnerChanged(vec![OwnerChanged {
+            old_owner,
+            new_owner,
+        }])
+    }
+
+    #[must_use]
+    pub fn release_deployed(
+        id: String,
+        target: AccountId,
+        method: String,
+        actor: AccountId,
+        success: bool,
+    ) -> Self {
+        Self::ReleaseDeployed(vec![ReleaseDeployed {
+            id,
+            target,
+            method,
+            actor,
+            success,
+        }])
+    }
+
+    #[must_use]
+    pub fn release_purged(id: String, size: u64, actor: AccountId) -> Self {
+        Self::ReleasePurged(vec![ReleasePurged { id, size, actor }])
+    }
+
+    #[must_use]
+    pub fn release_promoted(id: String, channel: String, actor: AccountId) -> Self {
+        Self::ReleasePromoted(vec![ReleasePromoted { id, channel, actor }])
+    }
+
+    #[must_use]
+    pub fn role_granted(account_id: AccountId, role: Role, actor: AccountId) -> Self {
+        Self::RoleGranted(vec![RoleChanged {
+            account_id,
+            role,
+            actor,
+        }])
+    }
+
+    #[must_use]
+    pub fn role_revoked(account_id: AccountId, role: Role, actor: AccountId) -> Self {
+        Self::RoleRevoked(vec![RoleChanged {
+            account_id,
+            role,
+            actor,
+        }])
+    }
+
+    /// Serializes the event into the NEP-297 format.
+    #[must_use]
+    pub fn to_log(&self) -> String {
+        let log = EventLog {
+            standard: STANDARD,
+            version: VERSION,
+            event: self,
+        };
+        // Serializing plain structs into JSON can't fail.
+        let json = serde_json::to_string(&log).unwrap_or_default();
+        format!("EVENT_JSON:{json}")
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&self.to_log());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(log: &str) -> serde_json::Value {
+        let json = log.strip_prefix("EVENT_JSON:").unwrap();
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_release_restored() {
+        let event = Event::release_restored(
+            "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            "owner.near".parse().unwrap(),
+            Some("yanked by mistake".to_string()),
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_restored",
+                "data": [{
+                    "id": "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "actor": "owner.near",
+                    "reason": "yanked by mistake",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_release_pushed() {
+        let metadata = ReleaseMetadata {
+            git_commit: Some("0f3a1c2".to_string()),
+            labels: vec!["audited".to_string()],
+            ..ReleaseMetadata::default()
+        };
+        let event = Event::release_pushed(
+            "engine/v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+                .to_string(),
+            1024,
+            "ci-bot.near".parse().unwrap(),
+            metadata,
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_pushed",
+                "data": [{
+                    "id": "engine/v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "size": 1024,
+                    "actor": "ci-bot.near",
+                    "metadata": {
+                        "git_commit": "0f3a1c2",
+                        "source_url": null,
+                        "toolchain": null,
+                        "build_command": null,
+                        "release_notes": null,
+                        "labels": ["audited"],
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_release_yanked() {
+        let event = Event::release_yanked(
+            "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            "yanker.near".parse().unwrap(),
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_yanked",
+                "data": [{
+                    "id": "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "actor": "yanker.near",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_latest_changed() {
+        let actor: AccountId = "ci-bot.near".parse().unwrap();
+        let event = Event::latest_changed(None, None, actor.clone());
+        assert_eq!(
+            parse(&event.to_log())["data"],
+            json!([{ "id": null, "actor": "ci-bot.near" }])
+        );
+
+        let event = Event::latest_changed(
+            Some("engine".to_string()),
+            Some(
+                "engine/v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+                    .to_string(),
+            ),
+            actor,
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "latest_changed",
+                "data": [{
+                    "package": "engine",
+                    "id": "engine/v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "actor": "ci-bot.near",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_release_deployed() {
+        let event = Event::release_deployed(
+            "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            "aurora".parse().unwrap(),
+            "upgrade".to_string(),
+            "deployer.near".parse().unwrap(),
+            false,
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_deployed",
+                "data": [{
+                    "id": "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "target": "aurora",
+                    "method": "upgrade",
+                    "actor": "deployer.near",
+                    "success": false,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_owner_changed() {
+        let event = Event::owner_changed(
+            "old-owner.near".parse().unwrap(),
+            "new-owner.near".parse().unwrap(),
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "owner_changed",
+                "data": [{
+                    "old_owner": "old-owner.near",
+                    "new_owner": "new-owner.near",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_release_purged() {
+        let event = Event::release_purged(
+            "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            2048,
+            "admin.near".parse().unwrap(),
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_purged",
+                "data": [{
+                    "id": "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "size": 2048,
+                    "actor": "admin.near",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_release_promoted() {
+        let event = Event::release_promoted(
+            "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            "stable".to_string(),
+            "ci-bot.near".parse().unwrap(),
+        );
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "release_promoted",
+                "data": [{
+                    "id": "v1.2.3-b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+                    "channel": "stable",
+                    "actor": "ci-bot.near",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_role_changed() {
+        let account_id: AccountId = "ci-bot.near".parse().unwrap();
+        let actor: AccountId = "admin.near".parse().unwrap();
+        let event = Event::role_granted(account_id.clone(), Role::Publisher, actor.clone());
+        assert_eq!(
+            parse(&event.to_log()),
+            json!({
+                "standard": STANDARD,
+                "version": VERSION,
+                "event": "role_granted",
+                "data": [{
+                    "account_id": "ci-bot.near",
+                    "role": "Publisher",
+                    "actor": "admin.near",
+                }],
+            })
+        );
+        let event = Event::role_revoked(account_id, Role::Publisher, actor);
+        assert_eq!(parse(&event.to_log())["event"], "role_revoked");
+    }
+}
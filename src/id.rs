@@ -1,19 +1,63 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::convert::TryFrom;
+use std::fmt;
 
 /// A checksum as bytes.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
 pub struct Checksum(pub Vec<u8>);
 
-impl ToString for Checksum {
-    fn to_string(&self) -> String {
-        hex::encode(&self.0)
+impl TryFrom<String> for Checksum {
+    type Error = error::IdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl TryFrom<&str> for Checksum {
+    type Error = error::IdError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        // Check to ensure that the hash is exactly 64 bytes long.
+        if value.len() != 64 {
+            return Err(error::IdError::HashLen);
+        }
+        Ok(Self(hex::decode(value)?))
     }
 }
 
-/// A version for the data included.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+impl Checksum {
+    /// Parses either a hex string or a base58 string, the way NEAR reports
+    /// the `code_hash` of an account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is neither a hex nor a base58 encoded
+    /// 32-byte hash.
+    pub fn from_hex_or_base58(value: &str) -> Result<Self, error::IdError> {
+        if value.len() == 64 {
+            return Self::try_from(value);
+        }
+        let bytes = near_sdk::bs58::decode(value)
+            .into_vec()
+            .map_err(|_| error::IdError::Base58)?;
+        if bytes.len() != 32 {
+            return Err(error::IdError::HashLen);
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+/// A version for the data included, following Semantic Versioning 2.0.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Version {
     /// The major version.
     major: u32,
@@ -21,16 +65,117 @@ pub struct Version {
     minor: u32,
     /// The patch version.
     patch: u32,
+    /// The dot separated pre-release identifiers, empty if there are none.
+    pre: String,
+    /// The dot separated build metadata identifiers, empty if there are none.
+    build: String,
+}
+
+impl Version {
+    /// Bit set in the serialized major version when the pre-release and
+    /// build metadata follow the patch version. Versions without them are
+    /// serialized exactly as before they were supported.
+    const EXTENDED_FLAG: u32 = 1 << 31;
+
+    #[must_use]
+    pub fn pre(&self) -> &str {
+        &self.pre
+    }
+
+    #[must_use]
+    pub fn build(&self) -> &str {
+        &self.build
+    }
+
+    /// Returns the version without build metadata, which has the same
+    /// precedence.
+    #[must_use]
+    pub fn without_build(&self) -> Self {
+        Self {
+            build: String::new(),
+            ..self.clone()
+        }
+    }
+
+    #[allow(clippy::missing_const_for_fn)]
+    #[must_use]
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Checks whether the version matches the semver requirement.
+    #[must_use]
+    pub fn matches(&self, req: &semver::VersionReq) -> bool {
+        // The identifiers are validated on parsing the same way semver does.
+        let version = semver::Version {
+            major: self.major.into(),
+            minor: self.minor.into(),
+            patch: self.patch.into(),
+            pre: semver::Prerelease::new(&self.pre).unwrap_or(semver::Prerelease::EMPTY),
+            build: semver::BuildMetadata::new(&self.build).unwrap_or(semver::BuildMetadata::EMPTY),
+        };
+        req.matches(&version)
+    }
+
+    fn parse_identifiers(value: &str, is_pre: bool) -> Result<(), error::VersionError> {
+        for identifier in value.split('.') {
+            if identifier.is_empty()
+                || !identifier
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            {
+                return Err(error::VersionError::InvalidIdentifier(
+                    identifier.to_string(),
+                ));
+            }
+            // Numeric pre-release identifiers must not include leading zeroes.
+            if is_pre
+                && identifier.len() > 1
+                && identifier.starts_with('0')
+                && identifier.bytes().all(|b| b.is_ascii_digit())
+            {
+                return Err(error::VersionError::InvalidIdentifier(
+                    identifier.to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TryFrom<String> for Version {
     type Error = error::VersionError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        // Split string value into parts, seperated by `.`
+        Self::try_from(&value[..])
+    }
+}
+
+impl TryFrom<&str> for Version {
+    type Error = error::VersionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         let Some(value) = value.strip_prefix('v') else {
             return Err(error::VersionError::UnusualVersion);
         };
+        // Split off the build metadata, following the first `+`, and then the
+        // pre-release, following the first `-`.
+        let (value, build) = match value.split_once('+') {
+            Some((value, build)) => {
+                Self::parse_identifiers(build, false)?;
+                (value, build)
+            }
+            None => (value, ""),
+        };
+        let (value, pre) = match value.split_once('-') {
+            Some((value, pre)) => {
+                Self::parse_identifiers(pre, true)?;
+                (value, pre)
+            }
+            None => (value, ""),
+        };
+
+        // Split string value into parts, seperated by `.`
         let parts: Vec<&str> = value.split_terminator('.').collect();
         // Check to ensure we have 3 parts.
         if parts.len() != 3 {
@@ -39,63 +184,164 @@ impl TryFrom<String> for Version {
 
         let major = parts[0]
             .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
+            .map_err(error::VersionError::ParseInt)?;
         let minor = parts[1]
             .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
+            .map_err(error::VersionError::ParseInt)?;
         let patch = parts[2]
             .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
+            .map_err(error::VersionError::ParseInt)?;
+        // The top bit of the major version is reserved for serialization,
+        // and setting all the bits marks a packaged Id.
+        if major >= !Self::EXTENDED_FLAG {
+            return Err(error::VersionError::MajorTooLarge);
+        }
 
         Ok(Self {
-            major: major?,
-            minor: minor?,
-            patch: patch?,
+            major,
+            minor,
+            patch,
+            pre: pre.to_string(),
+            build: build.to_string(),
         })
     }
 }
 
-impl TryFrom<&str> for Version {
-    type Error = error::VersionError;
+impl Ord for Version {
+    /// Orders versions by the semantic versioning precedence. Build metadata doesn't
+    /// affect the precedence and only breaks ties to keep the order total. The
+    /// index of versions is keyed by versions without build metadata, so ties
+    /// never happen in it.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A version without pre-release has a higher precedence.
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => cmp_identifiers(&self.pre, &other.pre),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        // Split string value into parts, seperated by `.`
-        let Some(value) = value.strip_prefix('v') else {
-            return Err(error::VersionError::UnusualVersion);
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares dot separated identifiers one by one. Numeric identifiers are
+/// compared numerically and have a lower precedence than alphanumeric ones.
+/// A larger set of identifiers has a higher precedence if all the preceding
+/// ones are equal.
+fn cmp_identifiers(a: &str, b: &str) -> Ordering {
+    let is_numeric = |identifier: &str| identifier.bytes().all(|b| b.is_ascii_digit());
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (is_numeric(a), is_numeric(b)) {
+                // Numeric identifiers have no leading zeroes, so the longer
+                // one is larger.
+                (true, true) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => a.cmp(b),
+            },
         };
-        let parts: Vec<&str> = value.split_terminator('.').collect();
-        // Check to ensure we have 3 parts.
-        if parts.len() != 3 {
-            return Err(error::VersionError::UnusualVersion);
+        if ordering != Ordering::Equal {
+            return ordering;
         }
+    }
+}
 
-        let major = parts[0]
-            .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
-        let minor = parts[1]
-            .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
-        let patch = parts[2]
-            .parse::<u32>()
-            .map_err(error::VersionError::ParseInt);
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre)?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build)?;
+        }
+        Ok(())
+    }
+}
 
+impl BorshSerialize for Version {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let extended = !self.pre.is_empty() || !self.build.is_empty();
+        let major = if extended {
+            self.major | Self::EXTENDED_FLAG
+        } else {
+            self.major
+        };
+        BorshSerialize::serialize(&major, writer)?;
+        BorshSerialize::serialize(&self.minor, writer)?;
+        BorshSerialize::serialize(&self.patch, writer)?;
+        if extended {
+            BorshSerialize::serialize(&self.pre, writer)?;
+            BorshSerialize::serialize(&self.build, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Version {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let major: u32 = BorshDeserialize::deserialize(buf)?;
+        let minor = BorshDeserialize::deserialize(buf)?;
+        let patch = BorshDeserialize::deserialize(buf)?;
+        let (pre, build) = if major & Self::EXTENDED_FLAG == 0 {
+            (String::new(), String::new())
+        } else {
+            (
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            )
+        };
         Ok(Self {
-            major: major?,
-            minor: minor?,
-            patch: patch?,
+            major: major & !Self::EXTENDED_FLAG,
+            minor,
+            patch,
+            pre,
+            build,
         })
     }
 }
 
-impl ToString for Version {
-    fn to_string(&self) -> String {
-        format!("v{}.{}.{}", self.major, self.minor, self.patch)
+/// Maximum length of a package name.
+pub const MAX_PACKAGE_LEN: usize = 64;
+
+/// Checks that the package name is made of lowercase ASCII letters, digits,
+/// `-` and `_`, and isn't longer than [`MAX_PACKAGE_LEN`].
+///
+/// # Errors
+///
+/// Returns an error if the package name is invalid.
+pub fn check_package(package: &str) -> Result<(), error::IdError> {
+    let is_valid = !package.is_empty()
+        && package.len() <= MAX_PACKAGE_LEN
+        && package
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+    if is_valid {
+        Ok(())
+    } else {
+        Err(error::IdError::InvalidPackage(package.to_string()))
     }
 }
 
 /// The Id of checksum data.
-#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Id {
+    /// The package of the data, `None` for the default package.
+    pub package: Option<String>,
     /// The version of the data.
     pub version: Version,
     /// The blake2b checksum of the data.
@@ -103,9 +349,66 @@ pub struct Id {
 }
 
 impl Id {
+    /// Value serialized in place of the major version in front of the
+    /// package name. Ids of the default package are serialized without it,
+    /// exactly as before packages were supported.
+    const PACKAGE_FLAG: u32 = u32::MAX;
+
     #[must_use]
     pub const fn new(version: Version, checksum: Checksum) -> Self {
-        Self { version, checksum }
+        Self {
+            package: None,
+            version,
+            checksum,
+        }
+    }
+
+    /// Moves the Id into the package.
+    #[must_use]
+    pub fn in_package(self, package: Option<String>) -> Self {
+        Self { package, ..self }
+    }
+
+    /// Checks the Id against an expected checksum, given either as a hex
+    /// string or as a full Id string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expected value can't be parsed.
+    pub fn matches(&self, expected: &str) -> Result<bool, error::IdError> {
+        if expected.contains('-') {
+            Ok(Self::try_from(expected)? == *self)
+        } else {
+            Ok(Checksum::try_from(expected)? == self.checksum)
+        }
+    }
+}
+
+impl BorshSerialize for Id {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if let Some(package) = &self.package {
+            BorshSerialize::serialize(&Self::PACKAGE_FLAG, writer)?;
+            BorshSerialize::serialize(package, writer)?;
+        }
+        BorshSerialize::serialize(&self.version, writer)?;
+        BorshSerialize::serialize(&self.checksum, writer)
+    }
+}
+
+impl BorshDeserialize for Id {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let package = match buf.get(..4) {
+            Some(flag) if flag == Self::PACKAGE_FLAG.to_le_bytes() => {
+                *buf = &buf[4..];
+                Some(BorshDeserialize::deserialize(buf)?)
+            }
+            _ => None,
+        };
+        Ok(Self {
+            package,
+            version: BorshDeserialize::deserialize(buf)?,
+            checksum: BorshDeserialize::deserialize(buf)?,
+        })
     }
 }
 
@@ -114,7 +417,11 @@ impl Serialize for Id {
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("Id", 2)?;
+        let mut s = serializer.serialize_struct("Id", 3)?;
+        match &self.package {
+            Some(package) => s.serialize_field("package", package)?,
+            None => s.skip_field("package")?,
+        }
         s.serialize_field("version", &self.version.to_string())?;
         s.serialize_field("checksum", &self.checksum.to_string())?;
         s.end()
@@ -133,35 +440,43 @@ impl TryFrom<&str> for Id {
     type Error = error::IdError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let parts: Vec<&str> = value.split_terminator('-').collect();
-
-        // Check to ensure only two parts exist.
-        if parts.len() != 2 {
+        // The package is separated by `/`, which the version can't contain.
+        let (package, value) = match value.split_once('/') {
+            Some((package, value)) => {
+                check_package(package)?;
+                (Some(package.to_string()), value)
+            }
+            None => (None, value),
+        };
+        // The checksum is hex encoded, so the last `-` always separates it
+        // from the version, which may contain `-` itself.
+        let Some((version, checksum)) = value.rsplit_once('-') else {
             return Err(error::IdError::UnusualId);
-        }
+        };
 
         // Check to ensure that the first part starts with a 'v' for
         // version.
-        if !parts[0].starts_with('v') {
+        if !version.starts_with('v') {
             return Err(error::IdError::MissingVPrefix);
         }
 
-        // Check to ensure that the 2nd part is exactly 64 bytes long.
-        if parts[1].len() != 64 {
-            return Err(error::IdError::HashLen);
-        }
-        let version = Version::try_from(parts[0])?;
-        let checksum = Checksum(hex::decode(parts[1])?);
+        let checksum = Checksum::try_from(checksum)?;
+        let version = Version::try_from(version)?;
 
-        Ok(Self { version, checksum })
+        Ok(Self {
+            package,
+            version,
+            checksum,
+        })
     }
 }
 
-impl ToString for Id {
-    fn to_string(&self) -> String {
-        let version = self.version.to_string();
-        let checksum = self.checksum.to_string();
-        format!("{version}-{checksum}")
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(package) = &self.package {
+            write!(f, "{package}/")?;
+        }
+        write!(f, "{}-{}", self.version, self.checksum)
     }
 }
 
@@ -171,10 +486,48 @@ pub struct IdStatus {
     pub status: Status,
 }
 
+/// Layout of an Id written before pre-release, build metadata and packages
+/// were supported, when any `u32` major version was accepted.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct LegacyId {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub checksum: Checksum,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyIdStatus {
+    pub id: LegacyId,
+    pub status: Status,
+}
+
+impl TryFrom<LegacyId> for Id {
+    type Error = error::VersionError;
+
+    /// Converts the legacy Id, which is serialized the same way as the Id,
+    /// unless its major version doesn't fit into the current encoding.
+    fn try_from(value: LegacyId) -> Result<Self, Self::Error> {
+        if value.major >= !Version::EXTENDED_FLAG {
+            return Err(error::VersionError::MajorTooLarge);
+        }
+        let version = Version {
+            major: value.major,
+            minor: value.minor,
+            patch: value.patch,
+            pre: String::new(),
+            build: String::new(),
+        };
+        Ok(Self::new(version, value.checksum))
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub enum Status {
     Released,
     Yanked,
+    /// Yanked, with the release data removed.
+    Purged,
 }
 
 pub mod error {
@@ -185,7 +538,11 @@ pub mod error {
     pub enum VersionError {
         #[error("error must fit into a u32")]
         ParseInt(#[from] ParseIntError),
-        #[error("unusual version provided, expected `vX.Y.Z`")]
+        #[error("major version must be less than 2147483647")]
+        MajorTooLarge,
+        #[error("invalid pre-release or build metadata identifier `{0}`")]
+        InvalidIdentifier(String),
+        #[error("unusual version provided, expected `vX.Y.Z[-PRE][+BUILD]`")]
         UnusualVersion,
     }
 
@@ -197,10 +554,14 @@ pub mod error {
         Version(#[from] VersionError),
         #[error("missing `v` as a prefix for version`")]
         MissingVPrefix,
-        #[error("id is incorrect, expected `vX.Y.Z-<hash>`")]
+        #[error("id is incorrect, expected `vX.Y.Z[-PRE][+BUILD]-<hash>`")]
         UnusualId,
         #[error("hash length must be 32 bytes")]
         HashLen,
+        #[error("hash is neither hex nor base58 encoded")]
+        Base58,
+        #[error("invalid package name `{0}`, expected lowercase letters, digits, `-` and `_`")]
+        InvalidPackage(String),
     }
 }
 
@@ -216,6 +577,8 @@ mod tests {
             major: 1,
             minor: 2,
             patch: 3,
+            pre: String::new(),
+            build: String::new(),
         };
 
         assert_eq!(version, expected);
@@ -247,6 +610,23 @@ mod tests {
         Id::try_from(id_string).unwrap();
     }
 
+    #[test]
+    fn test_id_matches() {
+        let checksum = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        let id = Id::try_from(format!("v1.2.3-{checksum}")).unwrap();
+
+        assert!(id.matches(checksum).unwrap());
+        assert!(id.matches(&id.to_string()).unwrap());
+        assert!(!id.matches(&format!("v1.2.4-{checksum}")).unwrap());
+        assert!(!id
+            .matches("a94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+            .unwrap());
+        assert!(matches!(
+            id.matches("b94d27b9"),
+            Err(error::IdError::HashLen)
+        ));
+    }
+
     #[test]
     fn test_bad_checksum_id() {
         let version = "v1.2.3".to_string();
@@ -254,4 +634,170 @@ mod tests {
         let id_string = format!("{version}-{checksum}");
         matches!(Id::try_from(id_string), Err(error::IdError::HashLen));
     }
+
+    #[test]
+    fn test_checksum_hex_or_base58() {
+        let hex = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        let checksum = Checksum::try_from(hex).unwrap();
+        let base58 = near_sdk::bs58::encode(&checksum.0).into_string();
+
+        assert_eq!(Checksum::from_hex_or_base58(hex).unwrap(), checksum);
+        assert_eq!(Checksum::from_hex_or_base58(&base58).unwrap(), checksum);
+        assert!(matches!(
+            Checksum::from_hex_or_base58("0OIl"),
+            Err(error::IdError::Base58)
+        ));
+        assert!(matches!(
+            Checksum::from_hex_or_base58(&base58[..20]),
+            Err(error::IdError::HashLen)
+        ));
+    }
+
+    #[test]
+    fn test_version_matches() {
+        let matches = |version: &str, req: &str| {
+            let req = semver::VersionReq::parse(req).unwrap();
+            Version::try_from(version).unwrap().matches(&req)
+        };
+        assert!(matches("v2.3.1", "^2"));
+        assert!(!matches("v3.0.0", "^2"));
+        assert!(matches("v1.4.7+build.1", "~1.4"));
+        assert!(matches("v1.5.0", ">=1.4, <1.6"));
+        assert!(!matches("v1.6.0", ">=1.4, <1.6"));
+        assert!(!matches("v2.1.0-rc.1", "^2"));
+        assert!(matches("v2.1.0-rc.2", ">=2.1.0-rc.1"));
+    }
+
+    #[test]
+    fn test_prerelease_and_build_version() {
+        let version = Version::try_from("v3.1.0-rc.1+build.5").unwrap();
+        assert_eq!(version.pre(), "rc.1");
+        assert_eq!(version.build(), "build.5");
+        assert!(version.is_prerelease());
+        assert_eq!(version.to_string(), "v3.1.0-rc.1+build.5");
+
+        let version = Version::try_from("v3.1.0+build-5.x").unwrap();
+        assert_eq!(version.pre(), "");
+        assert_eq!(version.build(), "build-5.x");
+        assert!(!version.is_prerelease());
+
+        let version = Version::try_from("v3.1.0-alpha-1").unwrap();
+        assert_eq!(version.pre(), "alpha-1");
+    }
+
+    #[test]
+    fn test_bad_prerelease_and_build_version() {
+        for version in [
+            "v3.1.0-",
+            "v3.1.0+",
+            "v3.1.0-rc..1",
+            "v3.1.0-01",
+            "v3.1.0-rc_1",
+            "v3.1.0+build+5",
+        ] {
+            assert!(
+                matches!(
+                    Version::try_from(version),
+                    Err(error::VersionError::InvalidIdentifier(_))
+                ),
+                "{version}"
+            );
+        }
+        for version in ["v2147483648.0.0", "v2147483647.0.0"] {
+            assert!(matches!(
+                Version::try_from(version),
+                Err(error::VersionError::MajorTooLarge)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_package_id() {
+        let checksum = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        let id_string = format!("eth-connector/v1.2.3-rc.1-{checksum}");
+        let id = Id::try_from(id_string.as_str()).unwrap();
+        assert_eq!(id.package.as_deref(), Some("eth-connector"));
+        assert_eq!(id.version.to_string(), "v1.2.3-rc.1");
+        assert_eq!(id.to_string(), id_string);
+
+        // Ids of the default package are serialized as before packages.
+        let legacy = Id::try_from(format!("v1.2.3-rc.1-{checksum}")).unwrap();
+        let bytes = id.try_to_vec().unwrap();
+        assert_eq!(Id::try_from_slice(&bytes).unwrap(), id);
+        assert_eq!(
+            bytes[4 + 4 + "eth-connector".len()..],
+            legacy.try_to_vec().unwrap()
+        );
+
+        for id_string in [
+            format!("Engine/v1.2.3-{checksum}"),
+            format!("/v1.2.3-{checksum}"),
+            format!("a.b/v1.2.3-{checksum}"),
+        ] {
+            assert!(
+                matches!(
+                    Id::try_from(id_string.as_str()),
+                    Err(error::IdError::InvalidPackage(_))
+                ),
+                "{id_string}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_prerelease_and_build_id() {
+        let checksum = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        let id_string = format!("v3.1.0-rc.1+build.5-{checksum}");
+        let id = Id::try_from(id_string.as_str()).unwrap();
+        assert_eq!(id.version.pre(), "rc.1");
+        assert_eq!(id.version.build(), "build.5");
+        assert_eq!(id.checksum.to_string(), checksum);
+        assert_eq!(id.to_string(), id_string);
+    }
+
+    #[test]
+    fn test_version_borsh_compatibility() {
+        // Serialized before pre-release and build metadata were supported.
+        let legacy = (1_u32, 2_u32, 3_u32).try_to_vec().unwrap();
+        let version = Version::try_from_slice(&legacy).unwrap();
+        assert_eq!(version, Version::try_from("v1.2.3").unwrap());
+        assert_eq!(version.try_to_vec().unwrap(), legacy);
+
+        let version = Version::try_from("v1.2.3-rc.1+build.5").unwrap();
+        let bytes = version.try_to_vec().unwrap();
+        assert_eq!(Version::try_from_slice(&bytes).unwrap(), version);
+
+        let checksum = Checksum(vec![0xAB; 32]);
+        let id = Id::new(version, checksum);
+        let bytes = id.try_to_vec().unwrap();
+        assert_eq!(Id::try_from_slice(&bytes).unwrap(), id);
+    }
+
+    #[test]
+    fn test_version_precedence() {
+        let versions = [
+            "v1.0.0-alpha",
+            "v1.0.0-alpha.1",
+            "v1.0.0-alpha.beta",
+            "v1.0.0-beta",
+            "v1.0.0-beta.2",
+            "v1.0.0-beta.11",
+            "v1.0.0-rc.1",
+            "v1.0.0",
+            "v1.0.1",
+            "v1.1.0",
+            "v2.0.0",
+            "v10.0.0",
+        ];
+        for pair in versions.windows(2) {
+            let lower = Version::try_from(pair[0]).unwrap();
+            let higher = Version::try_from(pair[1]).unwrap();
+            assert!(lower < higher, "{lower} < {higher}");
+        }
+
+        let plain = Version::try_from("v1.0.0").unwrap();
+        let build = Version::try_from("v1.0.0+build.5").unwrap();
+        assert_eq!(plain.cmp(&build), Ordering::Less);
+        assert!(build < Version::try_from("v1.0.1-alpha").unwrap());
+    }
 }
@@ -1,14 +1,36 @@
 #![deny(clippy::pedantic, clippy::nursery)]
 #![allow(clippy::module_name_repetitions)]
 
-use crate::id::{Checksum, Id, IdStatus, Version};
-use crate::storage::ReleaseStorage;
+use crate::deployment::{Deployment, DeploymentStorage, Outcome};
+use crate::eth::{EthAddress, EthSignature, EthSignerStorage};
+use crate::event::Event;
+use crate::id::{Checksum, Id, IdStatus, Status, Version};
+use crate::metadata::{PushOptions, PushRecord, ReleaseInfo, ReleaseMetadata};
+use crate::proposal::{Action, ApprovalPolicy, Proposal, ProposalStorage};
+use crate::roles::{Role, RoleStorage};
+use crate::signer::{ReleaseSignature, SignedIdStatus, SignerStorage};
+use crate::storage::{
+    Channel, LatestMode, LegacyReleaseStorage, ReleaseStorage, StorageKey, Tombstone,
+};
+use crate::upload::{UploadSession, UploadStorage};
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::json_types::Base64VecU8;
-use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
+use near_sdk::collections::UnorderedMap;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::{
+    env, near_bindgen, require, AccountId, Balance, Gas, GasWeight, PanicOnDefault, Promise,
+    PromiseOrValue, PromiseResult, PublicKey,
+};
 
+pub mod deployment;
+pub mod eth;
+pub mod event;
 pub mod id;
+pub mod metadata;
+pub mod proposal;
+pub mod roles;
+pub mod signer;
 pub mod storage;
+pub mod upload;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
 
@@ -21,6 +43,66 @@ pub struct ReleaseData(Vec<u8>);
 pub struct State {
     storage: ReleaseStorage,
     owner_id: AccountId,
+    pending_owner_id: Option<AccountId>,
+    roles: RoleStorage,
+    approval_policy: Option<ApprovalPolicy>,
+    proposals: ProposalStorage,
+    uploads: UploadStorage,
+    /// Storages of the named packages. Releases pushed without a package
+    /// belong to the default package kept in `storage`.
+    packages: UnorderedMap<String, ReleaseStorage>,
+    deployments: DeploymentStorage,
+    signers: SignerStorage,
+    eth_signers: EthSignerStorage,
+}
+
+/// Layout of the contract state before the storage indexes were added.
+#[derive(BorshDeserialize)]
+struct LegacyState {
+    storage: LegacyReleaseStorage,
+    owner_id: AccountId,
+}
+
+/// Key of the version of the state layout. It's kept apart from the state,
+/// which `near_bindgen` reads and writes as is.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Layouts of the contract state, each of which may be read and converted
+/// into the current one.
+#[allow(clippy::large_enum_variant)]
+enum VersionedState {
+    /// The layout before the storage indexes were added. No version is
+    /// stored along with it.
+    V0(LegacyState),
+    V1(State),
+}
+
+impl VersionedState {
+    const CURRENT: u8 = 1;
+
+    /// Reads the state in the layout of the stored version.
+    fn read() -> Self {
+        let state = match env::storage_read(STATE_VERSION_KEY).as_deref() {
+            None => env::state_read().map(Self::V0),
+            Some([1]) => env::state_read().map(Self::V1),
+            Some(version) => env::panic_str(&format!("Unknown state version {version:?}")),
+        };
+        state.unwrap_or_else(|| env::panic_str("State not found"))
+    }
+
+    fn into_current(self) -> State {
+        match self {
+            Self::V0(legacy) => State {
+                storage: legacy.storage.migrate().unwrap_or_panic(),
+                ..State::new(legacy.owner_id)
+            },
+            Self::V1(state) => state,
+        }
+    }
+
+    fn write_current_version() {
+        env::storage_write(STATE_VERSION_KEY, &[Self::CURRENT]);
+    }
 }
 
 #[near_bindgen]
@@ -28,10 +110,49 @@ impl State {
     #[must_use]
     #[init]
     pub fn new(owner_id: AccountId) -> Self {
+        VersionedState::write_current_version();
         Self {
             storage: ReleaseStorage::default(),
             owner_id,
+            pending_owner_id: None,
+            roles: RoleStorage::default(),
+            approval_policy: None,
+            proposals: ProposalStorage::default(),
+            uploads: UploadStorage::default(),
+            packages: UnorderedMap::new(StorageKey::Packages),
+            deployments: DeploymentStorage::default(),
+            signers: SignerStorage::default(),
+            eth_signers: EthSignerStorage::default(),
+        }
+    }
+
+    /// Migrates the state written by any previous version of the contract
+    /// into the current layout. The state written before the storage indexes
+    /// were added gets the indexes of the stored releases built, and fails to
+    /// migrate if it holds a major version too large for the current
+    /// encoding of versions.
+    #[must_use]
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = VersionedState::read().into_current();
+        VersionedState::write_current_version();
+        state
+    }
+
+    /// Deploys new code of the contract to its own account and migrates the
+    /// state. Both happen in a single batch, so a failed migration reverts
+    /// the deployment. In the proposal mode, the code is deployed only after
+    /// the proposal is approved, and the attached deposit must cover the
+    /// storage used by the proposal.
+    #[payable]
+    pub fn upgrade_self(&mut self, code: Base64VecU8) -> PromiseOrValue<()> {
+        require!(self.is_owner(), "Access denied: owner's method");
+        if self.approval_policy.is_some() {
+            self.propose_paid(Action::UpgradeSelf, Some(&ReleaseData(code.into())));
+            return PromiseOrValue::Value(());
         }
+        PromiseOrValue::Promise(upgrade(code.into()))
     }
 
     #[must_use]
@@ -44,71 +165,1524 @@ impl State {
         &self.owner_id
     }
 
-    /// Pushes a new release of the contract into the storage.
+    /// Get the account proposed as the next owner, if any.
+    #[must_use]
+    pub const fn get_pending_owner(&self) -> Option<&AccountId> {
+        self.pending_owner_id.as_ref()
+    }
+
+    /// Proposes a new owner. The ownership is transferred only after the
+    /// proposed account calls `accept_ownership`.
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        require!(self.is_owner(), "Access denied: owner's method");
+        self.pending_owner_id = Some(new_owner);
+    }
+
+    /// Accepts the ownership proposed by the current owner.
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&predecessor),
+            "Access denied: pending owner's method"
+        );
+        let old_owner = std::mem::replace(&mut self.owner_id, predecessor);
+        self.pending_owner_id = None;
+        Event::owner_changed(old_owner, self.owner_id.clone()).emit();
+    }
+
+    /// Cancels a pending ownership transfer.
+    #[payable]
+    pub fn cancel_ownership_transfer(&mut self) {
+        require!(self.is_owner(), "Access denied: owner's method");
+        require!(
+            self.pending_owner_id.is_some(),
+            "No pending ownership transfer"
+        );
+        self.pending_owner_id = None;
+    }
+
+    /// Check whether the account holds the role. The owner implicitly holds
+    /// every role.
+    #[must_use]
+    pub fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        account_id == &self.owner_id || self.roles.has_role(account_id, role)
+    }
+
+    /// Lists accounts the role was granted to.
+    #[must_use]
+    pub fn list_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.roles.members(role)
+    }
+
+    /// Grants the role to the account. In the proposal mode, the role is
+    /// granted only after the proposal is approved, and `false` is returned.
+    /// The attached deposit must cover the storage used by the proposal.
+    #[payable]
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) -> bool {
+        self.assert_role(Role::Admin);
+        if self.approval_policy.is_some() {
+            self.propose_paid(Action::GrantRole { account_id, role }, None);
+            return false;
+        }
+        self.grant(account_id, role, env::predecessor_account_id())
+    }
+
+    /// Revokes the role from the account. In the proposal mode, the role is
+    /// revoked only after the proposal is approved, and `false` is returned.
+    /// The attached deposit must cover the storage used by the proposal.
+    #[payable]
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) -> bool {
+        self.assert_role(Role::Admin);
+        if self.approval_policy.is_some() {
+            self.propose_paid(Action::RevokeRole { account_id, role }, None);
+            return false;
+        }
+        self.revoke(&account_id, role, env::predecessor_account_id())
+    }
+
+    /// Get the policy of approving pushes and yanks, if the proposal mode is on.
+    #[must_use]
+    pub const fn get_approval_policy(&self) -> Option<&ApprovalPolicy> {
+        self.approval_policy.as_ref()
+    }
+
+    /// Sets the policy of approving pushes and yanks. Passing `null` turns
+    /// the proposal mode off. In the proposal mode, the policy is replaced
+    /// only after the proposal is approved under the current policy, and the
+    /// attached deposit must cover the storage used by the proposal.
+    #[payable]
+    pub fn set_approval_policy(&mut self, policy: Option<ApprovalPolicy>) {
+        self.assert_role(Role::Admin);
+        if let Some(policy) = &policy {
+            require!(
+                policy.is_valid(),
+                "Threshold must be between 1 and the number of approvers"
+            );
+        }
+        if self.approval_policy.is_some() {
+            self.propose_paid(Action::SetApprovalPolicy { policy }, None);
+        } else {
+            self.approval_policy = policy;
+        }
+    }
+
+    /// Check whether the same data is rejected under a new version of the
+    /// package.
+    #[must_use]
+    pub fn get_unique_checksums(&self, package: Option<String>) -> bool {
+        self.read(package, ReleaseStorage::unique_checksums)
+            .unwrap_or_default()
+    }
+
+    /// Sets whether the same data is rejected under a new version of the
+    /// package. The attached deposit must cover the storage used by the
+    /// package if it's new, the excess is refunded to the caller.
+    #[payable]
+    pub fn set_unique_checksums(&mut self, unique_checksums: bool, package: Option<String>) {
+        self.assert_role(Role::Admin);
+        let package = check_package(package);
+        let storage_usage = env::storage_usage();
+        self.create_package(package.as_deref());
+        self.update(package.as_deref(), |storage| {
+            storage.set_unique_checksums(unique_checksums);
+        });
+        charge_storage(storage_usage).unwrap_or_panic();
+    }
+
+    /// Registers an ed25519 key of a release signer.
+    #[payable]
+    pub fn add_signer(&mut self, public_key: &PublicKey) -> bool {
+        require!(self.is_owner(), "Access denied: owner's method");
+        self.signers.add_signer(public_key).unwrap_or_panic()
+    }
+
+    /// Unregisters a release signer. Its signatures aren't reported anymore.
+    /// Fails if fewer signers than the required signatures would be left.
+    #[payable]
+    pub fn remove_signer(&mut self, public_key: &PublicKey) -> bool {
+        require!(self.is_owner(), "Access denied: owner's method");
+        self.signers.remove_signer(public_key).unwrap_or_panic()
+    }
+
+    /// Lists the registered release signers.
+    #[must_use]
+    pub fn list_signers(&self) -> Vec<PublicKey> {
+        self.signers.signers()
+    }
+
+    /// Sets the number of signatures of registered signers required to push
+    /// a release. It can't exceed the number of registered signers.
+    #[payable]
+    pub fn set_min_signatures(&mut self, min_signatures: u32) {
+        require!(self.is_owner(), "Access denied: owner's method");
+        self.signers
+            .set_min_signatures(min_signatures)
+            .unwrap_or_panic();
+    }
+
+    #[must_use]
+    pub const fn get_min_signatures(&self) -> u32 {
+        self.signers.min_signatures()
+    }
+
+    /// Registers the Ethereum address of a release signer.
     #[payable]
-    pub fn push(&mut self, version: String, code: Base64VecU8, latest: bool) -> String {
+    pub fn add_eth_signer(&mut self, address: EthAddress) -> bool {
         require!(self.is_owner(), "Access denied: owner's method");
+        self.eth_signers.add_signer(&address)
+    }
+
+    /// Unregisters an Ethereum release signer. Its attestations aren't
+    /// reported anymore.
+    #[payable]
+    pub fn remove_eth_signer(&mut self, address: EthAddress) -> bool {
+        require!(self.is_owner(), "Access denied: owner's method");
+        self.eth_signers.remove_signer(&address)
+    }
+
+    /// Lists the registered Ethereum release signers.
+    #[must_use]
+    pub fn list_eth_signers(&self) -> Vec<EthAddress> {
+        self.eth_signers.signers()
+    }
+
+    /// Lists the named packages. The default package isn't listed.
+    #[must_use]
+    pub fn list_packages(&self) -> Vec<String> {
+        self.packages.keys().collect()
+    }
+
+    /// Pushes a new release of the contract into the storage. In the
+    /// proposal mode, the release is stored only after the proposal is
+    /// approved. The optional arguments are grouped in `options`.
+    ///
+    /// The optional `expected_checksum`, either a hex string or a full Id,
+    /// must match the checksum computed from the code.
+    ///
+    /// Pushing the identical release again is a no-op, while pushing other
+    /// data under a released version fails. Versions differing only in
+    /// build metadata are the same version. The release is promoted to the
+    /// `channel` if it's given. Releases are pushed into the default
+    /// package unless `package` is given.
+    ///
+    /// The block height, timestamp and the pushing account are recorded
+    /// along with the optional `metadata` describing the build.
+    ///
+    /// The `signatures` of registered signers over the release Id are
+    /// verified and stored with the release, but only if the release is
+    /// new. There must be at least as many of them as `get_min_signatures`
+    /// requires. The `eth_signatures` are
+    /// EIP-191 signatures over the release Id, each of which must recover to
+    /// a registered Ethereum signer.
+    ///
+    /// The attached deposit must cover the storage used by the release, the
+    /// excess is refunded to the caller.
+    #[payable]
+    pub fn push(
+        &mut self,
+        version: String,
+        code: Base64VecU8,
+        latest: bool,
+        options: Option<PushOptions>,
+    ) -> String {
+        self.assert_role(Role::Publisher);
+        let PushOptions {
+            expected_checksum,
+            channel,
+            package,
+            metadata,
+            signatures,
+            eth_signatures,
+        } = options.unwrap_or_default();
         let code: Vec<u8> = code.into();
 
         let checksum = Checksum(env::sha256(&code));
         let id = {
-            let version = Version::try_from(version).unwrap();
-            Id::new(version, checksum)
+            let version = Version::try_from(version).unwrap_or_panic();
+            Id::new(version, checksum).in_package(check_package(package))
+        };
+        if let Some(expected) = expected_checksum {
+            verify_checksum(&id, expected).unwrap_or_panic();
+        }
+        let storage_usage = env::storage_usage();
+        self.publish(
+            id.clone(),
+            &ReleaseData(code),
+            latest,
+            channel,
+            metadata,
+            signatures,
+            &eth_signatures,
+        );
+        charge_storage(storage_usage).unwrap_or_panic();
+        id.to_string()
+    }
+    /// Begins an upload of release data too large to be pushed in a single
+    /// transaction. The data along with its length can't be longer than a
+    /// NEAR storage value may be. Returns the ID of the upload session.
+    #[payable]
+    pub fn begin_upload(
+        &mut self,
+        version: String,
+        total_len: u32,
+        expected_sha256: String,
+        package: Option<String>,
+    ) -> u64 {
+        self.assert_role(Role::Publisher);
+        require!(total_len > 0, "Total length must be positive");
+        require!(
+            total_len <= upload::MAX_RELEASE_LEN,
+            "Total length exceeds the limit of a storage value"
+        );
+
+        let id = {
+            let version = Version::try_from(version).unwrap_or_panic();
+            let checksum = Checksum::try_from(expected_sha256).unwrap_or_panic();
+            Id::new(version, checksum).in_package(check_package(package))
         };
-        self.storage.insert(id.clone(), &ReleaseData(code), latest);
+        self.check(&id);
+        let storage_usage = env::storage_usage();
+        let session = self
+            .uploads
+            .begin(env::predecessor_account_id(), id, total_len);
+        charge_storage(storage_usage).unwrap_or_panic();
+        session.id
+    }
+
+    /// Appends a chunk of release data to the upload session. The offset
+    /// must be equal to the number of bytes received so far.
+    #[payable]
+    pub fn append_chunk(&mut self, session_id: u64, offset: u32, bytes: Base64VecU8) {
+        let mut session = self.get_own_upload(session_id);
+        if offset != session.received_len {
+            env::panic_str(&format!(
+                "Unexpected offset, expected {}",
+                session.received_len
+            ));
+        }
+        let bytes: Vec<u8> = bytes.into();
+        require!(!bytes.is_empty(), "Chunk must not be empty");
+        let remaining = (session.total_len - session.received_len) as usize;
+        require!(
+            bytes.len() <= remaining,
+            "Chunk exceeds the total length of the upload"
+        );
+        let storage_usage = env::storage_usage();
+        self.uploads.append(&mut session, &bytes);
+        charge_storage(storage_usage).unwrap_or_panic();
+    }
+
+    /// Finalizes the upload session and pushes the release once the data
+    /// matches the expected length and checksum. The metadata and signatures
+    /// are handled the same way as by `push`. If the identical release is
+    /// already stored, the storage freed by the chunks is refunded.
+    #[payable]
+    pub fn finalize_upload(
+        &mut self,
+        session_id: u64,
+        latest: bool,
+        channel: Option<String>,
+        metadata: Option<ReleaseMetadata>,
+        signatures: Option<Vec<ReleaseSignature>>,
+        eth_signatures: Option<Vec<EthSignature>>,
+    ) -> String {
+        self.assert_role(Role::Publisher);
+        let session = self.get_own_upload(session_id);
+        require!(
+            session.received_len == session.total_len,
+            "Upload is incomplete"
+        );
+
+        let storage_usage = env::storage_usage();
+        let code = self.uploads.remove(&session);
+        let expected = session.release_id.checksum.to_string();
+        let id = Id::new(session.release_id.version, Checksum(env::sha256(&code.0)))
+            .in_package(session.release_id.package);
+        verify_checksum(&id, expected).unwrap_or_panic();
+        self.publish(
+            id.clone(),
+            &code,
+            latest,
+            channel,
+            metadata.unwrap_or_default(),
+            signatures.unwrap_or_default(),
+            &eth_signatures.unwrap_or_default(),
+        );
+        charge_storage(storage_usage).unwrap_or_panic();
+        refund_storage(storage_usage, session.uploader);
         id.to_string()
     }
 
-    /// Yanks a release from the storage with a provided ID.
+    /// Aborts the upload session and removes the data received in it.
+    /// Allowed for the uploader and admins. The freed storage is refunded
+    /// to the uploader.
+    #[payable]
+    pub fn abort_upload(&mut self, session_id: u64) {
+        let Some(session) = self.uploads.get(session_id) else {
+            env::panic_str("Upload session not found");
+        };
+        let account_id = env::predecessor_account_id();
+        require!(
+            session.uploader == account_id || self.has_role(&account_id, Role::Admin),
+            "Access denied: uploader's or admin's method"
+        );
+        let storage_usage = env::storage_usage();
+        self.uploads.remove(&session);
+        refund_storage(storage_usage, session.uploader);
+    }
+
+    /// Get upload session
+    #[must_use]
+    pub fn get_upload(&self, session_id: u64) -> Option<UploadSession> {
+        self.uploads.get(session_id)
+    }
+
+    /// Lists all unfinished upload sessions.
+    #[must_use]
+    pub fn list_uploads(&self) -> Vec<UploadSession> {
+        self.uploads.list()
+    }
+
+    /// Yanks a release from the storage with a provided ID. The release data
+    /// stays readable until it's purged. In the proposal mode, the release is
+    /// yanked only after the proposal is approved, and its current status is
+    /// returned. The attached deposit must cover the storage used by the
+    /// proposal.
     #[payable]
     pub fn pull(&mut self, id: String) -> Option<IdStatus> {
+        self.assert_role(Role::Yanker);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        if self.approval_policy.is_some() {
+            let id_status = self.get_release_status(&id)?;
+            self.propose_paid(Action::Pull { id }, None);
+            return Some(id_status);
+        }
+        self.yank(&id, env::predecessor_account_id())
+    }
+
+    /// Removes the data of a yanked release, leaving a tombstone with its
+    /// size and purge time. The freed storage is refunded to the caller. In
+    /// the proposal mode, the data is removed only after the proposal is
+    /// approved, the storage is refunded to the proposer, and `None` is
+    /// returned. The release is checked to be yanked before it's proposed.
+    /// The attached deposit must cover the storage used by the proposal.
+    #[payable]
+    pub fn purge(&mut self, id: String) -> Option<Tombstone> {
+        self.assert_role(Role::Admin);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        if self.approval_policy.is_some() {
+            self.read(id.package.clone(), |storage| storage.check_purge(&id))
+                .unwrap_or_else(|| Err(storage::error::StorageError::NotFound(id.to_string())))
+                .unwrap_or_panic();
+            self.propose_paid(Action::Purge { id }, None);
+            return None;
+        }
+        Some(self.purge_release(&id, env::predecessor_account_id()))
+    }
+
+    /// Get the tombstone of a purged release
+    #[must_use]
+    pub fn get_tombstone(&self, id: String) -> Option<Tombstone> {
+        let id = Id::try_from(id).unwrap_or_panic();
+        self.read(id.package.clone(), |storage| storage.get_tombstone(&id))
+            .flatten()
+    }
+
+    /// Restores a yanked release. If the release data was purged, it must
+    /// be provided again and match the checksum of the release, and the
+    /// attached deposit must cover the storage used by it. The excess is
+    /// refunded to the caller. In the proposal mode, the release is restored
+    /// only after the proposal is approved, and its current status is
+    /// returned.
+    #[payable]
+    pub fn restore(
+        &mut self,
+        id: String,
+        code: Option<Base64VecU8>,
+        reason: Option<String>,
+    ) -> IdStatus {
         require!(self.is_owner(), "Access denied: owner's method");
 
-        let id = Id::try_from(id).unwrap();
-        self.storage.remove(&id)
+        let id = Id::try_from(id).unwrap_or_panic();
+        let code = code.map(|code| {
+            let code: Vec<u8> = code.into();
+            let computed = Id::new(id.version.clone(), Checksum(env::sha256(&code)));
+            verify_checksum(&computed, id.checksum.to_string()).unwrap_or_panic();
+            ReleaseData(code)
+        });
+        let storage_usage = env::storage_usage();
+        let id_status = if self.approval_policy.is_some() {
+            let Some(id_status) = self.get_release_status(&id) else {
+                let error = storage::error::StorageError::NotFound(id.to_string());
+                env::panic_str(&error.to_string());
+            };
+            self.propose(Action::Restore { id, reason }, code.as_ref());
+            id_status
+        } else {
+            self.unyank(&id, code.as_ref(), reason, env::predecessor_account_id())
+        };
+        charge_storage(storage_usage).unwrap_or_panic();
+        id_status
+    }
+
+    /// Deploys the release to the target contract by calling its upgrade
+    /// method, such as `upgrade` or `stage_upgrade` of the Aurora engine.
+    /// The method is called with the release data followed by the optional
+    /// `extra_args`, the attached deposit and all the gas left once the
+    /// callback's `DEPLOY_CALLBACK_GAS` is reserved. The outcome is recorded
+    /// in the deployment registry and logged in a `release_deployed` event.
+    /// The storage used by the record is paid from the attached deposit, of
+    /// which `estimate_deploy_cost` is kept back and the rest is passed to
+    /// the target. The passed deposit is refunded if the target call fails.
+    ///
+    /// Only released, not yanked, releases may be deployed.
+    #[payable]
+    pub fn deploy_to(
+        &mut self,
+        target: AccountId,
+        id: String,
+        method: String,
+        extra_args: Option<Base64VecU8>,
+    ) -> Promise {
+        self.assert_role(Role::Deployer);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        let error = match self.get_release_status(&id) {
+            Some(id_status) if id_status.status == Status::Released => None,
+            Some(_) => Some(storage::error::StorageError::Yanked(id.to_string())),
+            None => Some(storage::error::StorageError::NotFound(id.to_string())),
+        };
+        if let Some(error) = error {
+            env::panic_str(&error.to_string());
+        }
+        let Some(ReleaseData(mut args)) = self
+            .read(id.package.clone(), |storage| storage.get(&id))
+            .flatten()
+        else {
+            env::panic_str("Release data is missing");
+        };
+        if let Some(extra_args) = extra_args {
+            args.extend(extra_args.0);
+        }
+        let reserve = Balance::from(deployment::record_size(&id)) * env::storage_byte_cost();
+        let attached = env::attached_deposit();
+        if attached < reserve {
+            let error = error::Error::InsufficientDeposit {
+                required: reserve,
+                attached,
+            };
+            env::panic_str(&error.to_string());
+        }
+        let forwarded = attached - reserve;
+        Promise::new(target.clone())
+            .function_call_weight(
+                method.clone(),
+                args,
+                forwarded,
+                Gas(0),
+                GasWeight::default(),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(DEPLOY_CALLBACK_GAS)
+                    .with_unused_gas_weight(0)
+                    .on_deployed(
+                        target,
+                        id.to_string(),
+                        method,
+                        env::predecessor_account_id(),
+                        reserve.into(),
+                        forwarded.into(),
+                    ),
+            )
+    }
+
+    /// Records and logs the outcome of the deployment. The part of the
+    /// `reserve` not used by the record is refunded to the deployer, along
+    /// with the `forwarded` deposit which a failed target call returns to
+    /// this contract. Returns `true` if the target call succeeded.
+    #[private]
+    pub fn on_deployed(
+        &mut self,
+        target: AccountId,
+        id: String,
+        method: String,
+        actor: AccountId,
+        reserve: U128,
+        forwarded: U128,
+    ) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let storage_usage = env::storage_usage();
+        self.deployments.record(&Deployment {
+            target: target.clone(),
+            id: Id::try_from(id.clone()).unwrap_or_panic(),
+            block_height: env::block_height().into(),
+            deployer: actor.clone(),
+            outcome: if success {
+                Outcome::Succeeded
+            } else {
+                Outcome::Failed
+            },
+        });
+        let used = env::storage_usage().saturating_sub(storage_usage);
+        let mut refund = reserve
+            .0
+            .saturating_sub(Balance::from(used) * env::storage_byte_cost());
+        if !success {
+            refund += forwarded.0;
+        }
+        if refund > 0 {
+            Promise::new(actor.clone()).transfer(refund);
+        }
+        Event::release_deployed(id, target, method, actor, success).emit();
+        success
     }
 
-    /// Get release status
+    /// Records a deployment of the release made without `deploy_to`, e.g.
+    /// by a DAO proposal. The attached deposit must cover the storage used
+    /// by the record, the excess is refunded to the caller.
+    #[payable]
+    pub fn record_deployment(&mut self, target: AccountId, id: String) {
+        self.assert_role(Role::Deployer);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        if self.get_release_status(&id).is_none() {
+            let error = storage::error::StorageError::NotFound(id.to_string());
+            env::panic_str(&error.to_string());
+        }
+        let storage_usage = env::storage_usage();
+        self.deployments.record(&Deployment {
+            target,
+            id,
+            block_height: env::block_height().into(),
+            deployer: env::predecessor_account_id(),
+            outcome: Outcome::Recorded,
+        });
+        charge_storage(storage_usage).unwrap_or_panic();
+    }
+
+    /// Lists the deployments of the release to all accounts. Comparing them
+    /// with `current_release_of` tells which accounts still run the release.
     #[must_use]
-    pub fn get_status(&self, id: String) -> Option<IdStatus> {
-        let id = Id::try_from(id).unwrap();
-        self.storage.get_status(id)
+    pub fn deployments_of(&self, id: String) -> Vec<Deployment> {
+        let id = Id::try_from(id).unwrap_or_panic();
+        self.deployments.of_release(&id)
+    }
+
+    /// Get the most recent deployment to the account which didn't fail,
+    /// telling the release the account runs.
+    #[must_use]
+    pub fn current_release_of(&self, account_id: &AccountId) -> Option<Deployment> {
+        self.deployments.current(account_id)
+    }
+
+    /// Approves the proposal. The proposal is executed once the threshold
+    /// of approvals is reached. Returns `true` if it was executed. Otherwise,
+    /// the attached deposit must cover the storage used by the approval.
+    ///
+    /// Once executed, the storage of the previous approvals is refunded to
+    /// the approvers, while the storage of the proposal pays for the storage
+    /// used by the action. The unused part of it is refunded to the proposer,
+    /// and the storage used beyond it is charged to the attached deposit.
+    #[payable]
+    pub fn approve(&mut self, proposal_id: u64) -> bool {
+        let Some(policy) = self.approval_policy.clone() else {
+            env::panic_str("Proposal mode is off");
+        };
+        let account_id = env::predecessor_account_id();
+        require!(
+            policy.is_approver(&account_id),
+            "Access denied: approver's method"
+        );
+        let Some(mut proposal) = self.proposals.get(proposal_id) else {
+            env::panic_str("Proposal not found");
+        };
+        require!(
+            !proposal.is_expired(env::block_timestamp()),
+            "Proposal has expired"
+        );
+        let storage_usage = env::storage_usage();
+        require!(
+            self.proposals.approve(&mut proposal, account_id.clone()),
+            "Proposal already approved by the account"
+        );
+
+        if proposal.approvals(&policy) < policy.threshold as usize {
+            charge_storage(storage_usage).unwrap_or_panic();
+            return false;
+        }
+        let storage_usage = env::storage_usage();
+        let code = self.proposals.remove(proposal_id);
+        let freed = storage_usage.saturating_sub(env::storage_usage());
+        // The approval just made is the last one and wasn't paid for.
+        let (approvals, _) = proposal.approvals.split_at(proposal.approvals.len() - 1);
+        let refunded = refund_approvals(approvals);
+        let paid = freed.saturating_sub(refunded + proposal::approval_size(&account_id));
+        let proposer = proposal.proposer.clone();
+        let storage_usage = env::storage_usage();
+        match proposal.action {
+            Action::Push {
+                id,
+                latest,
+                channel,
+                metadata,
+            } => {
+                let Some(code) = code else {
+                    env::panic_str("Release data of the proposal is missing");
+                };
+                self.release(&id, &code, latest, channel, &metadata, proposal.proposer);
+            }
+            Action::SignedPush {
+                id,
+                latest,
+                channel,
+                metadata,
+                signatures,
+                attestations,
+            } => {
+                let Some(code) = code else {
+                    env::panic_str("Release data of the proposal is missing");
+                };
+                if self.release(&id, &code, latest, channel, &metadata, proposal.proposer) {
+                    self.signers.add_signatures(&id, signatures);
+                    self.eth_signers.add_attestations(&id, attestations);
+                }
+            }
+            Action::Pull { id } => {
+                self.yank(&id, proposal.proposer);
+            }
+            Action::Restore { id, reason } => {
+                self.unyank(&id, code.as_ref(), reason, proposal.proposer);
+            }
+            Action::Purge { id } => {
+                self.purge_release(&id, proposal.proposer);
+            }
+            Action::SetLatest { package, id } => {
+                self.set_latest(package.as_deref(), id, proposal.proposer);
+            }
+            Action::Promote { id, channel } => self.promote_to(&id, &channel, proposal.proposer),
+            Action::SetApprovalPolicy { policy } => self.approval_policy = policy,
+            Action::GrantRole { account_id, role } => {
+                self.grant(account_id, role, proposal.proposer);
+            }
+            Action::RevokeRole { account_id, role } => {
+                self.revoke(&account_id, role, proposal.proposer);
+            }
+            Action::UpgradeSelf => {
+                let Some(ReleaseData(code)) = code else {
+                    env::panic_str("Code of the proposal is missing");
+                };
+                upgrade(code);
+            }
+        }
+        let used = env::storage_usage().saturating_sub(storage_usage);
+        refund_bytes(paid.saturating_sub(used), proposer);
+        charge_storage(storage_usage + paid).unwrap_or_panic();
+        true
+    }
+
+    /// Cancels the proposal. Allowed for the proposer and admins, or for
+    /// anyone once the proposal has expired. The storage of each approval is
+    /// refunded to the approver, and the rest of the freed storage to the
+    /// proposer.
+    #[payable]
+    pub fn cancel_proposal(&mut self, proposal_id: u64) {
+        let Some(proposal) = self.proposals.get(proposal_id) else {
+            env::panic_str("Proposal not found");
+        };
+        let account_id = env::predecessor_account_id();
+        require!(
+            proposal.proposer == account_id
+                || self.has_role(&account_id, Role::Admin)
+                || proposal.is_expired(env::block_timestamp()),
+            "Access denied: proposer's or admin's method"
+        );
+        let storage_usage = env::storage_usage();
+        self.proposals.remove(proposal_id);
+        let freed = storage_usage.saturating_sub(env::storage_usage());
+        let refunded = refund_approvals(&proposal.approvals);
+        refund_bytes(freed.saturating_sub(refunded), proposal.proposer);
+    }
+
+    /// Estimates the deposit required to push a release with the data of
+    /// the given length and the given number of `signatures`, counting both
+    /// the ed25519 and the Ethereum ones, but without metadata.
+    #[allow(clippy::unused_self)]
+    #[must_use]
+    pub fn estimate_push_cost(&self, code_len: u32, signatures: Option<u32>) -> U128 {
+        let bytes = u64::from(code_len)
+            + storage::RELEASE_OVERHEAD
+            + u64::from(signatures.unwrap_or_default()) * signer::SIGNATURE_OVERHEAD;
+        (Balance::from(bytes) * env::storage_byte_cost()).into()
+    }
+
+    /// Estimates the deposit kept back by `deploy_to` to record the
+    /// deployment of the release. The part not used is refunded.
+    #[must_use]
+    pub fn estimate_deploy_cost(&self, id: String) -> U128 {
+        let id = Id::try_from(id).unwrap_or_panic();
+        (Balance::from(deployment::record_size(&id)) * env::storage_byte_cost()).into()
+    }
+
+    /// Get pending proposal
+    #[must_use]
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(proposal_id)
+    }
+
+    /// Lists pending proposals which haven't expired yet. Expired proposals
+    /// are still returned by `get_proposal` until they are cancelled.
+    #[must_use]
+    pub fn list_proposals(&self) -> Vec<Proposal> {
+        let now = env::block_timestamp();
+        self.proposals
+            .list()
+            .into_iter()
+            .filter(|proposal| !proposal.is_expired(now))
+            .collect()
+    }
+
+    /// Get release status along with the registered signers which signed
+    /// the release and the registered Ethereum signers which attested to it.
+    #[must_use]
+    pub fn get_status(&self, id: String) -> Option<SignedIdStatus> {
+        let id = Id::try_from(id).unwrap_or_panic();
+        let id_status = self.get_release_status(&id)?;
+        Some(SignedIdStatus {
+            id_status,
+            signed_by: self.signers.signed_by(&id),
+            attested_by: self.eth_signers.attested_by(&id),
+        })
+    }
+
+    /// Get the status, push record and metadata of the release, without its
+    /// data.
+    #[must_use]
+    pub fn get_release_info(&self, id: String) -> Option<ReleaseInfo> {
+        let id = Id::try_from(id).unwrap_or_panic();
+        self.read(id.package.clone(), |storage| storage.info(&id))?
+    }
+
+    /// Replaces the metadata of the release. The attached deposit must cover
+    /// the storage used by the metadata, the excess is refunded to the
+    /// caller.
+    #[allow(clippy::needless_pass_by_value)]
+    #[payable]
+    pub fn set_metadata(&mut self, id: String, metadata: ReleaseMetadata) {
+        self.assert_role(Role::MetadataEditor);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        metadata.check().unwrap_or_panic();
+        let storage_usage = env::storage_usage();
+        self.update_existing(id.package.as_deref(), |storage| {
+            storage.set_metadata(&id, &metadata)
+        })
+        .unwrap_or_panic();
+        charge_storage(storage_usage).unwrap_or_panic();
     }
 
     /// Get blob data for specific release
     #[must_use]
     pub fn get_blob(&self, id: String) -> Option<Base64VecU8> {
-        let id = Id::try_from(id).unwrap();
-        let release_data = self.storage.get(&id)?;
+        let id = Id::try_from(id).unwrap_or_panic();
+        let release_data = self.read(id.package.clone(), |storage| storage.get(&id))??;
         Some(release_data.0.into())
     }
 
-    /// Lists all releases.
+    /// Get release status by its version
+    #[must_use]
+    pub fn get_by_version(&self, version: String, package: Option<String>) -> Option<IdStatus> {
+        let version = Version::try_from(version).unwrap_or_panic();
+        self.read(package, |storage| storage.get_by_version(&version))?
+    }
+
+    /// Get statuses of the releases with specific checksum, given either as
+    /// a hex string or as a base58 `code_hash` reported by NEAR RPC
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn get_by_checksum(&self, checksum: String, package: Option<String>) -> Vec<IdStatus> {
+        let checksum = Checksum::from_hex_or_base58(&checksum).unwrap_or_panic();
+        self.read(package, |storage| storage.get_by_checksum(&checksum))
+            .unwrap_or_default()
+    }
+
+    /// Lists releases which versions match the semver requirement, such as
+    /// `^2` or `>=1.4, <1.6`, in the version precedence order. Pre-releases
+    /// match only if the requirement mentions a pre-release of the same
+    /// version. The releases may be filtered by status, and only the highest
+    /// one is returned if `max_only` is set.
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn list_matching(
+        &self,
+        requirement: String,
+        status: Option<Status>,
+        max_only: Option<bool>,
+        package: Option<String>,
+    ) -> Vec<IdStatus> {
+        let req = semver::VersionReq::parse(&requirement)
+            .map_err(error::Error::VersionReq)
+            .unwrap_or_panic();
+        self.read(package, |storage| {
+            storage.matching(&req, status.as_ref(), max_only.unwrap_or_default())
+        })
+        .unwrap_or_default()
+    }
+
+    /// Get blob data for the release of specific version
+    #[must_use]
+    pub fn get_blob_by_version(
+        &self,
+        version: String,
+        package: Option<String>,
+    ) -> Option<Base64VecU8> {
+        let version = Version::try_from(version).unwrap_or_panic();
+        let release_data = self.read(package, |storage| {
+            let id_status = storage.get_by_version(&version)?;
+            storage.get(&id_status.id)
+        })??;
+        Some(release_data.0.into())
+    }
+
+    /// Lists releases in the order they were pushed, or newest first if
+    /// `reverse` is set. Returns at most `limit` releases starting from
+    /// `from_index`.
+    #[must_use]
+    pub fn list(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+        reverse: Option<bool>,
+        package: Option<String>,
+    ) -> Vec<IdStatus> {
+        self.read(package, |storage| {
+            storage.list(
+                from_index.unwrap_or_default(),
+                limit.unwrap_or(DEFAULT_PAGE_LIMIT),
+                reverse.unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default()
+    }
+
+    /// Get the number of releases
+    #[must_use]
+    pub fn count(&self, package: Option<String>) -> u64 {
+        self.read(package, ReleaseStorage::count)
+            .unwrap_or_default()
+    }
+
+    /// Lists yanked releases in the order they were yanked, or newest first
+    /// if `reverse` is set. Restoring a release moves the most recently
+    /// yanked one into its place. Returns at most `limit` releases starting
+    /// from `from_index`.
     #[must_use]
-    pub fn list(self) -> Vec<IdStatus> {
-        self.storage.list()
+    pub fn yank_list(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+        reverse: Option<bool>,
+        package: Option<String>,
+    ) -> Vec<Id> {
+        self.read(package, |storage| {
+            storage.yanks(
+                from_index.unwrap_or_default(),
+                limit.unwrap_or(DEFAULT_PAGE_LIMIT),
+                reverse.unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default()
     }
 
-    /// Lists all yank releases.
+    /// Get the number of yanked releases
     #[must_use]
-    pub fn yank_list(self) -> Vec<Id> {
-        self.storage.yanks()
+    pub fn yank_count(&self, package: Option<String>) -> u64 {
+        self.read(package, ReleaseStorage::yank_count)
+            .unwrap_or_default()
     }
 
-    /// Get latest version
+    /// Get latest version. Unless it's pinned, it's the highest released
+    /// version which is not a pre-release.
     #[must_use]
-    pub fn latest(&self) -> Option<Id> {
-        self.storage.latest()
+    pub fn latest(&self, package: Option<String>) -> Option<Id> {
+        self.read(package, ReleaseStorage::latest)?
+    }
+
+    /// Get whether the latest version is pinned or computed.
+    #[must_use]
+    pub fn get_latest_mode(&self, package: Option<String>) -> LatestMode {
+        self.read(package, ReleaseStorage::latest_mode)
+            .unwrap_or(LatestMode::Computed)
+    }
+
+    /// Pins the release as the latest one. In the proposal mode, the release
+    /// is pinned only after the proposal is approved, and the attached
+    /// deposit must cover the storage used by the proposal.
+    #[payable]
+    pub fn pin_latest(&mut self, id: String) {
+        self.assert_role(Role::Publisher);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        let package = id.package.clone();
+        if self.approval_policy.is_some() {
+            self.propose_paid(
+                Action::SetLatest {
+                    package,
+                    id: Some(id),
+                },
+                None,
+            );
+        } else {
+            self.set_latest(package.as_deref(), Some(id), env::predecessor_account_id());
+        }
+    }
+
+    /// Promotes the release to the channel, making it the latest release of
+    /// the channel. In the proposal mode, the release is promoted only after
+    /// the proposal is approved, and the attached deposit must cover the
+    /// storage used by the proposal.
+    #[payable]
+    pub fn promote(&mut self, id: String, channel: String) {
+        self.assert_role(Role::Publisher);
+
+        let id = Id::try_from(id).unwrap_or_panic();
+        if self.approval_policy.is_some() {
+            storage::check_channel(&channel).unwrap_or_panic();
+            self.propose_paid(Action::Promote { id, channel }, None);
+        } else {
+            self.promote_to(&id, &channel, env::predecessor_account_id());
+        }
+    }
+
+    /// Get the latest release of the channel. If the most recently promoted
+    /// release is yanked, the release promoted before it is the latest one.
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn latest_in(&self, channel: String, package: Option<String>) -> Option<Id> {
+        self.read(package, |storage| storage.latest_in(&channel))?
+    }
+
+    /// Lists all channels along with their latest releases.
+    #[must_use]
+    pub fn list_channels(&self, package: Option<String>) -> Vec<Channel> {
+        self.read(package, ReleaseStorage::channels)
+            .unwrap_or_default()
+    }
+
+    /// Unpins the latest release, so that it's computed from the released
+    /// versions. In the proposal mode, it's unpinned only after the proposal
+    /// is approved, and the attached deposit must cover the storage used by
+    /// the proposal.
+    #[payable]
+    pub fn unpin_latest(&mut self, package: Option<String>) {
+        self.assert_role(Role::Publisher);
+
+        let package = check_package(package);
+        if self.approval_policy.is_some() {
+            self.propose_paid(Action::SetLatest { package, id: None }, None);
+        } else {
+            self.set_latest(package.as_deref(), None, env::predecessor_account_id());
+        }
+    }
+}
+
+impl State {
+    /// Pushes the release, or proposes to push it in the proposal mode.
+    /// Pushing an already stored release is a no-op. The verified signatures
+    /// are stored only along with a new release.
+    #[allow(clippy::too_many_arguments)]
+    fn publish(
+        &mut self,
+        id: Id,
+        code: &ReleaseData,
+        latest: bool,
+        channel: Option<String>,
+        metadata: ReleaseMetadata,
+        signatures: Vec<ReleaseSignature>,
+        eth_signatures: &[EthSignature],
+    ) {
+        if let Some(channel) = &channel {
+            storage::check_channel(channel).unwrap_or_panic();
+        }
+        metadata.check().unwrap_or_panic();
+        let signatures = self.signers.verify(&id, signatures).unwrap_or_panic();
+        let attestations = self
+            .eth_signers
+            .verify(&id, eth_signatures)
+            .unwrap_or_panic();
+        if self.approval_policy.is_some() {
+            if self.check(&id) {
+                let action = if signatures.is_empty() && attestations.is_empty() {
+                    Action::Push {
+                        id,
+                        latest,
+                        channel,
+                        metadata,
+                    }
+                } else {
+                    Action::SignedPush {
+                        id,
+                        latest,
+                        channel,
+                        metadata,
+                        signatures,
+                        attestations,
+                    }
+                };
+                self.propose(action, Some(code));
+            }
+        } else {
+            let pushed_by = env::predecessor_account_id();
+            if self.release(&id, code, latest, channel, &metadata, pushed_by) {
+                self.signers.add_signatures(&id, signatures);
+                self.eth_signers.add_attestations(&id, attestations);
+            }
+        }
+    }
+
+    /// Stores the release along with its push record and metadata, and
+    /// promotes it to the channel. Nothing is changed if the identical
+    /// release is already stored. Logs the push and the change of the latest
+    /// release, if any. Returns `true` if the release was stored.
+    fn release(
+        &mut self,
+        id: &Id,
+        code: &ReleaseData,
+        latest: bool,
+        channel: Option<String>,
+        metadata: &ReleaseMetadata,
+        pushed_by: AccountId,
+    ) -> bool {
+        self.create_package(id.package.as_deref());
+        let latest_before = self.latest_of(id.package.as_deref());
+        let record = PushRecord {
+            size: code.0.len() as u64,
+            block_height: env::block_height().into(),
+            timestamp: env::block_timestamp().into(),
+            pushed_by,
+        };
+        let inserted = self
+            .update_existing(id.package.as_deref(), |storage| {
+                let inserted = storage.insert(id.clone(), code, latest)?;
+                if inserted {
+                    storage.record_push(id, &record, metadata);
+                }
+                Ok::<_, storage::error::StorageError>(inserted)
+            })
+            .unwrap_or_panic();
+        if !inserted {
+            return false;
+        }
+        let PushRecord {
+            size, pushed_by, ..
+        } = record;
+        Event::release_pushed(id.to_string(), size, pushed_by.clone(), metadata.clone()).emit();
+        self.log_latest_change(
+            id.package.as_deref(),
+            latest_before.as_ref(),
+            pushed_by.clone(),
+        );
+        if let Some(channel) = channel {
+            self.promote_to(id, &channel, pushed_by);
+        }
+        true
+    }
+
+    /// Yanks the release, logging the yank and the change of the latest
+    /// release, if any.
+    fn yank(&mut self, id: &Id, actor: AccountId) -> Option<IdStatus> {
+        let id_status = self.get_release_status(id)?;
+        if id_status.status != Status::Released {
+            return Some(id_status);
+        }
+        let latest = self.latest_of(id.package.as_deref());
+        let id_status = self.update(id.package.as_deref(), |storage| storage.remove(id))?;
+        Event::release_yanked(id.to_string(), actor.clone()).emit();
+        self.log_latest_change(id.package.as_deref(), latest.as_ref(), actor);
+        id_status
+    }
+
+    /// Removes the data of the yanked release, refunding the freed storage
+    /// to the account purging it, and logs the purge.
+    fn purge_release(&mut self, id: &Id, purged_by: AccountId) -> Tombstone {
+        let storage_usage = env::storage_usage();
+        let tombstone = self
+            .update_existing(id.package.as_deref(), |storage| {
+                storage.purge(id, env::block_timestamp(), purged_by.clone())
+            })
+            .unwrap_or_panic();
+        refund_storage(storage_usage, purged_by.clone());
+        Event::release_purged(id.to_string(), tombstone.size, purged_by).emit();
+        tombstone
+    }
+
+    /// Restores the yanked release, logging the restoration and the change
+    /// of the latest release, if any.
+    fn unyank(
+        &mut self,
+        id: &Id,
+        code: Option<&ReleaseData>,
+        reason: Option<String>,
+        actor: AccountId,
+    ) -> IdStatus {
+        let latest = self.latest_of(id.package.as_deref());
+        let id_status = self
+            .update_existing(id.package.as_deref(), |storage| storage.restore(id, code))
+            .unwrap_or_panic();
+        Event::release_restored(id.to_string(), actor.clone(), reason).emit();
+        self.log_latest_change(id.package.as_deref(), latest.as_ref(), actor);
+        id_status
+    }
+
+    fn set_latest(&mut self, package: Option<&str>, id: Option<Id>, actor: AccountId) {
+        let latest = self.latest_of(package);
+        self.update_existing(package, |storage| {
+            if let Some(id) = id {
+                storage.pin_latest(id)
+            } else {
+                storage.unpin_latest();
+                Ok(())
+            }
+        })
+        .unwrap_or_panic();
+        self.log_latest_change(package, latest.as_ref(), actor);
+    }
+
+    fn latest_of(&self, package: Option<&str>) -> Option<Id> {
+        self.read(package.map(ToString::to_string), ReleaseStorage::latest)?
+    }
+
+    /// Logs a `latest_changed` event if the latest release of the package
+    /// differs from `before`.
+    fn log_latest_change(&self, package: Option<&str>, before: Option<&Id>, actor: AccountId) {
+        let latest = self.latest_of(package);
+        if latest.as_ref() != before {
+            let id = latest.map(|id| id.to_string());
+            Event::latest_changed(package.map(ToString::to_string), id, actor).emit();
+        }
+    }
+
+    /// Promotes the release to the channel, logging the promotion if the
+    /// release wasn't the latest one of the channel yet.
+    fn promote_to(&mut self, id: &Id, channel: &str, actor: AccountId) {
+        let latest = self
+            .update_existing(id.package.as_deref(), |storage| {
+                let latest = storage.latest_in(channel);
+                storage.promote(id, channel).map(|()| latest)
+            })
+            .unwrap_or_panic();
+        if latest.as_ref() != Some(id) {
+            Event::release_promoted(id.to_string(), channel.to_string(), actor).emit();
+        }
+    }
+
+    /// Grants the role to the account, logging the grant if the account
+    /// didn't have the role yet.
+    fn grant(&mut self, account_id: AccountId, role: Role, actor: AccountId) -> bool {
+        let granted = self.roles.grant(account_id.clone(), role);
+        if granted {
+            Event::role_granted(account_id, role, actor).emit();
+        }
+        granted
+    }
+
+    /// Revokes the role from the account, logging the revocation if the
+    /// account had the role.
+    fn revoke(&mut self, account_id: &AccountId, role: Role, actor: AccountId) -> bool {
+        let revoked = self.roles.revoke(account_id, role);
+        if revoked {
+            Event::role_revoked(account_id.clone(), role, actor).emit();
+        }
+        revoked
+    }
+
+    /// Checks whether the release can be pushed. Returns `false` if the
+    /// identical release is already stored.
+    fn check(&self, id: &Id) -> bool {
+        self.read(id.package.clone(), |storage| storage.check(id))
+            .unwrap_or(Ok(true))
+            .unwrap_or_panic()
+    }
+
+    fn get_release_status(&self, id: &Id) -> Option<IdStatus> {
+        self.read(id.package.clone(), |storage| storage.get_status(id.clone()))?
+    }
+
+    /// Runs `f` over the storage of the package, if the package exists.
+    fn read<T>(&self, package: Option<String>, f: impl FnOnce(&ReleaseStorage) -> T) -> Option<T> {
+        match package {
+            None => Some(f(&self.storage)),
+            Some(package) => self.packages.get(&package).map(|storage| f(&storage)),
+        }
+    }
+
+    /// Runs `f` over the storage of the package and saves the changes, if
+    /// the package exists.
+    fn update<T>(
+        &mut self,
+        package: Option<&str>,
+        f: impl FnOnce(&mut ReleaseStorage) -> T,
+    ) -> Option<T> {
+        let Some(package) = package else {
+            return Some(f(&mut self.storage));
+        };
+        let package = package.to_string();
+        let mut storage = self.packages.get(&package)?;
+        let result = f(&mut storage);
+        self.packages.insert(&package, &storage);
+        Some(result)
+    }
+
+    /// Runs a fallible `f` over the storage of the package, failing if the
+    /// package doesn't exist.
+    fn update_existing<T, E>(
+        &mut self,
+        package: Option<&str>,
+        f: impl FnOnce(&mut ReleaseStorage) -> std::result::Result<T, E>,
+    ) -> Result<T>
+    where
+        error::Error: From<E>,
+    {
+        let result = self.update(package, f).ok_or_else(|| {
+            error::Error::PackageNotFound(package.unwrap_or_default().to_string())
+        })?;
+        Ok(result?)
+    }
+
+    fn create_package(&mut self, package: Option<&str>) {
+        if let Some(package) = package {
+            let package = package.to_string();
+            if self.packages.get(&package).is_none() {
+                self.packages
+                    .insert(&package, &ReleaseStorage::for_package(&package));
+            }
+        }
+    }
+
+    fn propose(&mut self, action: Action, code: Option<&ReleaseData>) -> Proposal {
+        let Some(policy) = &self.approval_policy else {
+            env::panic_str("Proposal mode is off");
+        };
+        let expires_at = env::block_timestamp().saturating_add(policy.ttl.0);
+        self.proposals
+            .create(action, code, env::predecessor_account_id(), expires_at)
+    }
+
+    /// Creates the proposal, requiring the attached deposit to cover the
+    /// storage used by it. The storage is refunded to the proposer once the
+    /// proposal is cancelled.
+    fn propose_paid(&mut self, action: Action, code: Option<&ReleaseData>) {
+        let storage_usage = env::storage_usage();
+        self.propose(action, code);
+        charge_storage(storage_usage).unwrap_or_panic();
+    }
+
+    fn get_own_upload(&self, session_id: u64) -> UploadSession {
+        let Some(session) = self.uploads.get(session_id) else {
+            env::panic_str("Upload session not found");
+        };
+        require!(
+            session.uploader == env::predecessor_account_id(),
+            "Access denied: uploader's method"
+        );
+        session
+    }
+
+    fn assert_role(&self, role: Role) {
+        if !self.has_role(&env::predecessor_account_id(), role) {
+            env::panic_str(&format!("Access denied: {role:?} role required"));
+        }
+    }
+}
+
+/// Validates the package name given by the caller.
+fn check_package(package: Option<String>) -> Option<String> {
+    if let Some(package) = &package {
+        id::check_package(package).unwrap_or_panic();
+    }
+    package
+}
+
+/// Deploys the code to the contract account and migrates the state in a
+/// single batch.
+fn upgrade(code: Vec<u8>) -> Promise {
+    Promise::new(env::current_account_id())
+        .deploy_contract(code)
+        .function_call_weight(
+            "migrate".to_string(),
+            vec![],
+            0,
+            Gas(0),
+            GasWeight::default(),
+        )
+}
+
+/// Gas reserved for the callback of a deployment.
+const DEPLOY_CALLBACK_GAS: Gas = Gas(10_000_000_000_000);
+
+/// Number of items returned by the list views if no limit is given.
+const DEFAULT_PAGE_LIMIT: u64 = 100;
+
+/// Checks that the computed Id matches the checksum expected by the caller.
+fn verify_checksum(id: &Id, expected: String) -> Result<()> {
+    if id.matches(&expected)? {
+        return Ok(());
+    }
+    Err(error::Error::ChecksumMismatch {
+        expected,
+        computed: id.checksum.to_string(),
+    })
+}
+
+/// Requires the attached deposit to cover the storage used since
+/// `initial_usage` and refunds the excess to the caller.
+fn charge_storage(initial_usage: u64) -> Result<()> {
+    let used = env::storage_usage().saturating_sub(initial_usage);
+    let required = Balance::from(used) * env::storage_byte_cost();
+    let attached = env::attached_deposit();
+    if attached < required {
+        return Err(error::Error::InsufficientDeposit { required, attached });
+    }
+    let refund = attached - required;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+    Ok(())
+}
+
+/// Refunds the storage freed since `initial_usage` to the account.
+fn refund_storage(initial_usage: u64, account_id: AccountId) {
+    refund_bytes(
+        initial_usage.saturating_sub(env::storage_usage()),
+        account_id,
+    );
+}
+
+/// Refunds the cost of storing the number of bytes to the account.
+fn refund_bytes(bytes: u64, account_id: AccountId) {
+    if bytes > 0 {
+        Promise::new(account_id).transfer(Balance::from(bytes) * env::storage_byte_cost());
+    }
+}
+
+/// Refunds the storage of each approval to the approver. Returns the number
+/// of bytes refunded.
+fn refund_approvals(approvals: &[AccountId]) -> u64 {
+    approvals
+        .iter()
+        .map(|approver| {
+            let size = proposal::approval_size(approver);
+            refund_bytes(size, approver.clone());
+            size
+        })
+        .sum()
+}
+
+/// Unwraps a result, aborting the execution with the error message.
+trait UnwrapOrPanic<T> {
+    fn unwrap_or_panic(self) -> T;
+}
+
+impl<T, E: std::fmt::Display> UnwrapOrPanic<T> for std::result::Result<T, E> {
+    fn unwrap_or_panic(self) -> T {
+        self.unwrap_or_else(|e| env::panic_str(&e.to_string()))
     }
 }
 
 mod error {
     use crate::id::error::IdError;
+    use crate::storage::error::StorageError;
     use thiserror::Error;
 
     #[derive(Error, Debug)]
     pub enum Error {
         #[error(transparent)]
         IdError(#[from] IdError),
+        #[error(transparent)]
+        StorageError(#[from] StorageError),
+        #[error("checksum mismatch: expected `{expected}`, computed `{computed}`")]
+        ChecksumMismatch { expected: String, computed: String },
+        #[error(
+            "insufficient deposit: {required} yoctoNEAR required for storage, {attached} attached"
+        )]
+        InsufficientDeposit { required: u128, attached: u128 },
+        #[error("invalid version requirement: {0}")]
+        VersionReq(semver::Error),
+        #[error("package `{0}` is not found")]
+        PackageNotFound(String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::collections::{LookupMap, Vector};
+
+    /// Layout of the release storage written before the state was versioned.
+    #[derive(BorshSerialize)]
+    struct UnversionedStorage {
+        releases: LookupMap<Id, ReleaseData>,
+        status_list: Vector<IdStatus>,
+        yanked_list: Vector<Id>,
+        latest: Option<Id>,
+    }
+
+    #[derive(BorshSerialize)]
+    struct UnversionedState {
+        storage: UnversionedStorage,
+        owner_id: AccountId,
+    }
+
+    fn id(version: &str, checksum: u8) -> Id {
+        Id::new(
+            Version::try_from(version).unwrap(),
+            Checksum(vec![checksum; 32]),
+        )
+    }
+
+    #[test]
+    fn test_migrate_unversioned_state() {
+        let released = id("v1.0.0", 1);
+        let yanked = id("v1.1.0", 2);
+        let mut storage = UnversionedStorage {
+            releases: LookupMap::new(StorageKey::BlobData),
+            status_list: Vector::new(StorageKey::StatusList),
+            yanked_list: Vector::new(StorageKey::YankedList),
+            latest: Some(released.clone()),
+        };
+        for (id, status) in [(&released, Status::Released), (&yanked, Status::Yanked)] {
+            storage.releases.insert(id, &ReleaseData(vec![1, 2, 3]));
+            storage.status_list.push(&IdStatus {
+                id: id.clone(),
+                status,
+            });
+        }
+        storage.yanked_list.push(&yanked);
+        env::state_write(&UnversionedState {
+            storage,
+            owner_id: "owner.near".parse().unwrap(),
+        });
+
+        let state = State::migrate();
+        assert_eq!(state.get_owner().as_str(), "owner.near");
+        assert_eq!(state.count(None), 2);
+        assert_eq!(state.latest(None), Some(released.clone()));
+        assert_eq!(state.yank_list(None, None, None, None), vec![yanked]);
+        let id_status = state.get_by_version("v1.0.0".to_string(), None);
+        assert_eq!(id_status.map(|id_status| id_status.id), Some(released));
+        assert_eq!(
+            env::storage_read(STATE_VERSION_KEY),
+            Some(vec![VersionedState::CURRENT])
+        );
+
+        //== The current layout is kept as is
+        env::state_write(&state);
+        let state = State::migrate();
+        assert_eq!(state.count(None), 2);
+        assert_eq!(state.get_owner().as_str(), "owner.near");
     }
 }
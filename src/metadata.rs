@@ -0,0 +1,161 @@
+use crate::eth::EthSignature;
+use crate::id::{Id, Status};
+use crate::signer::ReleaseSignature;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+
+/// Maximum length of a metadata field, except the release notes.
+pub const MAX_FIELD_LEN: usize = 256;
+/// Maximum length of the release notes.
+pub const MAX_NOTES_LEN: usize = 4096;
+/// Maximum number of labels of a release.
+pub const MAX_LABELS: usize = 16;
+
+/// Information about how a release was built, provided by the publisher.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
+#[serde(default)]
+pub struct ReleaseMetadata {
+    /// Hash of the git commit the release was built from.
+    pub git_commit: Option<String>,
+    /// URL of the source repository.
+    pub source_url: Option<String>,
+    /// Version of the toolchain used to build the release.
+    pub toolchain: Option<String>,
+    /// Command used to build the release.
+    pub build_command: Option<String>,
+    pub release_notes: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl ReleaseMetadata {
+    /// Checks that the fields fit into their limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first field which is too long, or if
+    /// there are too many labels.
+    pub fn check(&self) -> Result<(), error::MetadataError> {
+        let fields = [
+            ("git_commit", &self.git_commit),
+            ("source_url", &self.source_url),
+            ("toolchain", &self.toolchain),
+            ("build_command", &self.build_command),
+        ];
+        for (field, value) in fields {
+            check_len(field, value.as_deref().unwrap_or_default(), MAX_FIELD_LEN)?;
+        }
+        check_len(
+            "release_notes",
+            self.release_notes.as_deref().unwrap_or_default(),
+            MAX_NOTES_LEN,
+        )?;
+        if self.labels.len() > MAX_LABELS {
+            return Err(error::MetadataError::TooManyLabels(MAX_LABELS));
+        }
+        for label in &self.labels {
+            check_len("labels", label, MAX_FIELD_LEN)?;
+        }
+        Ok(())
+    }
+}
+
+const fn check_len(
+    field: &'static str,
+    value: &str,
+    max: usize,
+) -> Result<(), error::MetadataError> {
+    if value.len() > max {
+        return Err(error::MetadataError::TooLong { field, max });
+    }
+    Ok(())
+}
+
+/// Optional arguments of `push`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushOptions {
+    /// Either a hex string or a full Id, which must match the checksum
+    /// computed from the code.
+    pub expected_checksum: Option<String>,
+    /// Channel to promote the release to.
+    pub channel: Option<String>,
+    /// Package to push the release into, the default one if not given.
+    pub package: Option<String>,
+    pub metadata: ReleaseMetadata,
+    /// Signatures of registered signers over the release Id.
+    pub signatures: Vec<ReleaseSignature>,
+    /// EIP-191 signatures of registered Ethereum signers over the release Id.
+    pub eth_signatures: Vec<EthSignature>,
+}
+
+/// Information recorded by the contract on pushing a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct PushRecord {
+    /// Size of the release data in bytes.
+    pub size: u64,
+    /// Height of the block the release was stored in.
+    pub block_height: U64,
+    /// Block timestamp in nanoseconds when the release was stored.
+    pub timestamp: U64,
+    /// The account which pushed the release, or proposed to push it in the
+    /// proposal mode.
+    pub pushed_by: AccountId,
+}
+
+/// Everything known about a release, except its data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseInfo {
+    pub id: Id,
+    pub status: Status,
+    /// Missing for releases pushed before the records were kept.
+    pub pushed: Option<PushRecord>,
+    pub metadata: ReleaseMetadata,
+}
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum MetadataError {
+        #[error("metadata field `{field}` must not be longer than {max} bytes")]
+        TooLong { field: &'static str, max: usize },
+        #[error("release must not have more than {0} labels")]
+        TooManyLabels(usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_limits() {
+        let mut metadata = ReleaseMetadata {
+            git_commit: Some("0f3a1c2".to_string()),
+            labels: vec!["audited".to_string(); MAX_LABELS],
+            release_notes: Some("x".repeat(MAX_NOTES_LEN)),
+            ..ReleaseMetadata::default()
+        };
+        assert!(metadata.check().is_ok());
+
+        metadata.labels.push("extra".to_string());
+        assert!(matches!(
+            metadata.check(),
+            Err(error::MetadataError::TooManyLabels(MAX_LABELS))
+        ));
+        metadata.labels.clear();
+
+        metadata.toolchain = Some("x".repeat(MAX_FIELD_LEN + 1));
+        assert!(matches!(
+            metadata.check(),
+            Err(error::MetadataError::TooLong {
+                field: "toolchain",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::eth::Attestation;
+use crate::id::Id;
+use crate::metadata::ReleaseMetadata;
+use crate::roles::Role;
+use crate::signer::ReleaseSignature;
+use crate::storage::StorageKey;
+use crate::ReleaseData;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+
+/// Rules for approving proposals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct ApprovalPolicy {
+    /// Accounts allowed to approve proposals.
+    pub approvers: Vec<AccountId>,
+    /// Number of approvals needed to execute a proposal.
+    pub threshold: u32,
+    /// Time in nanoseconds a proposal stays open for approvals.
+    pub ttl: U64,
+}
+
+impl ApprovalPolicy {
+    #[allow(clippy::missing_const_for_fn)]
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let approvers = self.approvers.len();
+        self.threshold > 0 && (self.threshold as usize) <= approvers
+    }
+
+    #[must_use]
+    pub fn is_approver(&self, account_id: &AccountId) -> bool {
+        self.approvers.contains(account_id)
+    }
+}
+
+/// An action executed once the proposal is approved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub enum Action {
+    Push {
+        id: Id,
+        latest: bool,
+        channel: Option<String>,
+        metadata: ReleaseMetadata,
+    },
+    Pull {
+        id: Id,
+    },
+    /// Pins the release as the latest one, or unpins the latest release of
+    /// the package.
+    SetLatest {
+        package: Option<String>,
+        id: Option<Id>,
+    },
+    /// Promotes the release to the channel.
+    Promote {
+        id: Id,
+        channel: String,
+    },
+    /// Replaces the approval policy, or turns the proposal mode off.
+    SetApprovalPolicy {
+        policy: Option<ApprovalPolicy>,
+    },
+    GrantRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    RevokeRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    /// Deploys the code held by the proposal to the contract account and
+    /// migrates the state.
+    UpgradeSelf,
+    /// Restores the yanked release, with the data held by the proposal if
+    /// the release was purged.
+    Restore {
+        id: Id,
+        reason: Option<String>,
+    },
+    /// Removes the data of the yanked release.
+    Purge {
+        id: Id,
+    },
+    /// Pushes the release along with the verified signatures over it, which
+    /// are stored only if the release is new.
+    SignedPush {
+        id: Id,
+        latest: bool,
+        channel: Option<String>,
+        metadata: ReleaseMetadata,
+        signatures: Vec<ReleaseSignature>,
+        attestations: Vec<Attestation>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct Proposal {
+    pub id: u64,
+    pub action: Action,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    /// Block timestamp in nanoseconds after which the proposal can't be approved.
+    pub expires_at: U64,
+}
+
+impl Proposal {
+    #[must_use]
+    pub const fn is_expired(&self, now: u64) -> bool {
+        now > self.expires_at.0
+    }
+
+    /// Number of approvals given by the approvers of the policy.
+    #[must_use]
+    pub fn approvals(&self, policy: &ApprovalPolicy) -> usize {
+        self.approvals
+            .iter()
+            .filter(|account_id| policy.is_approver(account_id))
+            .count()
+    }
+}
+
+/// Returns the number of bytes used by the approval of the account, which
+/// is stored as a Borsh string in the list of approvals.
+#[must_use]
+pub fn approval_size(account_id: &AccountId) -> u64 {
+    4 + account_id.as_str().len() as u64
+}
+
+/// Wrapper over NEAR `UnorderedMap` to keep pending proposals along with
+/// the release data they are going to push.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ProposalStorage {
+    proposals: UnorderedMap<u64, Proposal>,
+    blobs: LookupMap<u64, ReleaseData>,
+    next_id: u64,
+}
+
+impl ProposalStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(
+        &mut self,
+        action: Action,
+        code: Option<&ReleaseData>,
+        proposer: AccountId,
+        expires_at: u64,
+    ) -> Proposal {
+        let proposal = Proposal {
+            id: self.next_id,
+            action,
+            proposer,
+            approvals: vec![],
+            expires_at: expires_at.into(),
+        };
+        self.next_id += 1;
+        self.proposals.insert(&proposal.id, &proposal);
+        if let Some(code) = code {
+            self.blobs.insert(&proposal.id, code);
+        }
+        proposal
+    }
+
+    /// Records the approval. Returns `false` if the account already approved it.
+    pub fn approve(&mut self, proposal: &mut Proposal, account_id: AccountId) -> bool {
+        if proposal.approvals.contains(&account_id) {
+            return false;
+        }
+        proposal.approvals.push(account_id);
+        self.proposals.insert(&proposal.id, proposal);
+        true
+    }
+
+    /// Removes the proposal, returning the release data it holds.
+    pub fn remove(&mut self, proposal_id: u64) -> Option<ReleaseData> {
+        self.proposals.remove(&proposal_id);
+        self.blobs.remove(&proposal_id)
+    }
+
+    #[must_use]
+    pub fn get(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(&proposal_id)
+    }
+
+    #[must_use]
+    pub fn list(&self) -> Vec<Proposal> {
+        self.proposals.values().collect()
+    }
+}
+
+impl Default for ProposalStorage {
+    fn default() -> Self {
+        Self {
+            proposals: UnorderedMap::new(StorageKey::Proposals),
+            blobs: LookupMap::new(StorageKey::ProposalBlobs),
+            next_id: 0,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::storage::StorageKey;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+
+/// A role which grants access to a group of contract methods.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
+pub enum Role {
+    /// Grants and revokes roles.
+    Admin,
+    /// Pushes new releases.
+    Publisher,
+    /// Yanks releases.
+    Yanker,
+    /// Edits metadata of releases.
+    MetadataEditor,
+    /// Deploys releases to other contracts.
+    Deployer,
+}
+
+/// Wrapper over NEAR `LookupMap` to grant, revoke and check roles of accounts.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RoleStorage {
+    members: LookupMap<Role, Vec<AccountId>>,
+}
+
+impl RoleStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants the role to the account. Returns `false` if the account
+    /// already had the role.
+    pub fn grant(&mut self, account_id: AccountId, role: Role) -> bool {
+        let mut members = self.members(role);
+        if members.contains(&account_id) {
+            return false;
+        }
+        members.push(account_id);
+        self.members.insert(&role, &members);
+        true
+    }
+
+    /// Revokes the role from the account. Returns `false` if the account
+    /// didn't have the role.
+    pub fn revoke(&mut self, account_id: &AccountId, role: Role) -> bool {
+        let mut members = self.members(role);
+        let Some(i) = members.iter().position(|member| member == account_id) else {
+            return false;
+        };
+        members.swap_remove(i);
+        self.members.insert(&role, &members);
+        true
+    }
+
+    #[must_use]
+    pub fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.members(role).contains(account_id)
+    }
+
+    #[must_use]
+    pub fn members(&self, role: Role) -> Vec<AccountId> {
+        self.members.get(&role).unwrap_or_default()
+    }
+}
+
+impl Default for RoleStorage {
+    fn default() -> Self {
+        Self {
+            members: LookupMap::new(StorageKey::RoleMembers),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::eth::EthAddress;
+use crate::id::{Id, IdStatus};
+use crate::storage::StorageKey;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{CurveType, PublicKey};
+
+/// Estimated number of bytes used by a signature or an Ethereum attestation
+/// pushed with a release.
+///
+/// Covers the signature and the key of its signer, as well as the record
+/// keeping the signatures of the release, or the proposal holding them in
+/// the proposal mode.
+pub const SIGNATURE_OVERHEAD: u64 = 256;
+
+/// A detached ed25519 signature over the string form of a release Id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct ReleaseSignature {
+    pub public_key: PublicKey,
+    pub signature: Base64VecU8,
+}
+
+/// Status of a release along with the registered signers which signed it.
+#[derive(Serialize, Eq, PartialEq)]
+pub struct SignedIdStatus {
+    #[serde(flatten)]
+    pub id_status: IdStatus,
+    pub signed_by: Vec<PublicKey>,
+    /// Registered Ethereum signers which attested to the release.
+    pub attested_by: Vec<EthAddress>,
+}
+
+/// Wrapper over NEAR `UnorderedSet` to keep the registered signers and the
+/// signatures of releases.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SignerStorage {
+    signers: UnorderedSet<PublicKey>,
+    signatures: LookupMap<Id, Vec<ReleaseSignature>>,
+    /// Number of signatures of registered signers required to push a release.
+    min_signatures: u32,
+}
+
+impl SignerStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the signer. Returns `false` if it was already registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key isn't an ed25519 key.
+    pub fn add_signer(&mut self, public_key: &PublicKey) -> Result<bool, error::SignerError> {
+        if public_key.curve_type() != CurveType::ED25519 {
+            return Err(error::SignerError::NotEd25519(public_key_string(
+                public_key,
+            )));
+        }
+        Ok(self.signers.insert(public_key))
+    }
+
+    /// Unregisters the signer. Signatures made by it are kept, but aren't
+    /// reported anymore. Returns `false` if it wasn't registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer signers than the required number of
+    /// signatures would be left.
+    pub fn remove_signer(&mut self, public_key: &PublicKey) -> Result<bool, error::SignerError> {
+        if !self.signers.contains(public_key) {
+            return Ok(false);
+        }
+        check_signers(self.min_signatures, self.signers.len() - 1)?;
+        Ok(self.signers.remove(public_key))
+    }
+
+    #[must_use]
+    pub fn signers(&self) -> Vec<PublicKey> {
+        self.signers.to_vec()
+    }
+
+    /// Sets the number of signatures of registered signers required to push
+    /// a release.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer signers are registered.
+    pub fn set_min_signatures(&mut self, min_signatures: u32) -> Result<(), error::SignerError> {
+        check_signers(min_signatures, self.signers.len())?;
+        self.min_signatures = min_signatures;
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn min_signatures(&self) -> u32 {
+        self.min_signatures
+    }
+
+    /// Verifies the signatures over the release Id and checks that there are
+    /// enough of them. Repeated signatures of the same signer are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a signer isn't registered, if a signature is
+    /// invalid, or if there are fewer signatures than required.
+    pub fn verify(
+        &self,
+        id: &Id,
+        signatures: Vec<ReleaseSignature>,
+    ) -> Result<Vec<ReleaseSignature>, error::SignerError> {
+        let message = id.to_string();
+        let mut verified: Vec<ReleaseSignature> = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            if verified
+                .iter()
+                .any(|other| other.public_key == signature.public_key)
+            {
+                continue;
+            }
+            let public_key = public_key_string(&signature.public_key);
+            if !self.signers.contains(&signature.public_key) {
+                return Err(error::SignerError::UnknownSigner(public_key));
+            }
+            // Registered keys are ed25519 ones, prefixed by the curve type.
+            let key = <&[u8; 32]>::try_from(&signature.public_key.as_bytes()[1..]);
+            let sig = <&[u8; 64]>::try_from(signature.signature.0.as_slice());
+            match (key, sig) {
+                (Ok(key), Ok(sig)) if ed25519_verify(sig, message.as_bytes(), key) => {
+                    verified.push(signature);
+                }
+                _ => return Err(error::SignerError::InvalidSignature(public_key)),
+            }
+        }
+        let provided = verified.len() as u64;
+        if provided < u64::from(self.min_signatures) {
+            return Err(error::SignerError::NotEnoughSignatures {
+                required: self.min_signatures,
+                provided,
+            });
+        }
+        Ok(verified)
+    }
+
+    /// Stores the verified signatures of the release along with the ones
+    /// stored before.
+    pub fn add_signatures(&mut self, id: &Id, signatures: Vec<ReleaseSignature>) {
+        if signatures.is_empty() {
+            return;
+        }
+        let mut stored = self.signatures.get(id).unwrap_or_default();
+        for signature in signatures {
+            if !stored
+                .iter()
+                .any(|other| other.public_key == signature.public_key)
+            {
+                stored.push(signature);
+            }
+        }
+        self.signatures.insert(id, &stored);
+    }
+
+    /// Returns the registered signers which signed the release.
+    #[must_use]
+    pub fn signed_by(&self, id: &Id) -> Vec<PublicKey> {
+        self.signatures
+            .get(id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|signature| signature.public_key)
+            .filter(|public_key| self.signers.contains(public_key))
+            .collect()
+    }
+}
+
+impl Default for SignerStorage {
+    fn default() -> Self {
+        Self {
+            signers: UnorderedSet::new(StorageKey::Signers),
+            signatures: LookupMap::new(StorageKey::Signatures),
+            min_signatures: 0,
+        }
+    }
+}
+
+/// Checks that the registered signers are enough to make the required
+/// number of signatures.
+fn check_signers(required: u32, signers: u64) -> Result<(), error::SignerError> {
+    if signers < u64::from(required) {
+        return Err(error::SignerError::TooFewSigners { required, signers });
+    }
+    Ok(())
+}
+
+fn public_key_string(public_key: &PublicKey) -> String {
+    near_sdk::serde_json::to_string(public_key).unwrap_or_default()
+}
+
+/// Verifies the ed25519 signature with the host function, which near-sdk
+/// 4.1 doesn't wrap yet.
+#[cfg(target_arch = "wasm32")]
+fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    // SAFETY: the lengths match the buffers, which outlive the call.
+    unsafe {
+        near_sdk::sys::ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            message.len() as u64,
+            message.as_ptr() as u64,
+            public_key.len() as u64,
+            public_key.as_ptr() as u64,
+        ) == 1
+    }
+}
+
+/// Verifies the ed25519 signature natively, since the mocked blockchain of
+/// near-sdk 4.1 lacks the host function.
+#[cfg(not(target_arch = "wasm32"))]
+fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    let Ok(signature) = ed25519_dalek::Signature::try_from(&signature[..]) else {
+        return false;
+    };
+    ed25519_dalek::PublicKey::from_bytes(public_key)
+        .is_ok_and(|public_key| public_key.verify_strict(message, &signature).is_ok())
+}
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum SignerError {
+        #[error("key {0} is not an ed25519 key")]
+        NotEd25519(String),
+        #[error("signer {0} is not registered")]
+        UnknownSigner(String),
+        #[error("invalid signature of signer {0}")]
+        InvalidSignature(String),
+        #[error("{required} signatures of registered signers required, {provided} provided")]
+        NotEnoughSignatures { required: u32, provided: u64 },
+        #[error("{required} signatures required, but {signers} signers registered")]
+        TooFewSigners { required: u32, signers: u64 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::{Checksum, Version};
+    use ed25519_dalek::{ExpandedSecretKey, PublicKey as DalekPublicKey, SecretKey};
+
+    fn signer(seed: u8) -> (PublicKey, impl Fn(&Id) -> ReleaseSignature) {
+        let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = DalekPublicKey::from(&secret);
+        let mut bytes = vec![CurveType::ED25519 as u8];
+        bytes.extend(public.to_bytes());
+        let public_key = PublicKey::try_from(bytes).unwrap();
+        let key = public_key.clone();
+        let sign = move |id: &Id| {
+            let signature =
+                ExpandedSecretKey::from(&secret).sign(id.to_string().as_bytes(), &public);
+            ReleaseSignature {
+                public_key: key.clone(),
+                signature: Base64VecU8(signature.to_bytes().to_vec()),
+            }
+        };
+        (public_key, sign)
+    }
+
+    #[test]
+    fn test_verify_signatures() {
+        let id = Id::new(Version::try_from("v1.0.0").unwrap(), Checksum(vec![1; 32]));
+        let other = Id::new(Version::try_from("v1.0.1").unwrap(), Checksum(vec![2; 32]));
+        let (alice, alice_signs) = signer(1);
+        let (_, bob_signs) = signer(2);
+        let mut storage = SignerStorage::new();
+        assert!(matches!(
+            storage.set_min_signatures(1),
+            Err(error::SignerError::TooFewSigners {
+                required: 1,
+                signers: 0
+            })
+        ));
+        assert!(storage.add_signer(&alice).unwrap());
+        storage.set_min_signatures(1).unwrap();
+
+        assert!(matches!(
+            storage.verify(&id, vec![]),
+            Err(error::SignerError::NotEnoughSignatures {
+                required: 1,
+                provided: 0
+            })
+        ));
+        assert!(matches!(
+            storage.verify(&id, vec![bob_signs(&id)]),
+            Err(error::SignerError::UnknownSigner(_))
+        ));
+        assert!(matches!(
+            storage.verify(&id, vec![alice_signs(&other)]),
+            Err(error::SignerError::InvalidSignature(_))
+        ));
+
+        let verified = storage
+            .verify(&id, vec![alice_signs(&id), alice_signs(&id)])
+            .unwrap();
+        assert_eq!(verified.len(), 1);
+        storage.add_signatures(&id, verified);
+        assert_eq!(storage.signed_by(&id), vec![alice.clone()]);
+
+        //== Signatures of unregistered signers aren't reported
+        assert!(storage.remove_signer(&alice).is_err());
+        storage.set_min_signatures(0).unwrap();
+        assert!(storage.remove_signer(&alice).unwrap());
+        assert_eq!(storage.signed_by(&id), Vec::<PublicKey>::new());
+    }
+}
@@ -1,14 +1,90 @@
-use crate::id::IdStatus;
+use crate::id::{Checksum, IdStatus, LegacyId, LegacyIdStatus, Version};
+use crate::metadata::{PushRecord, ReleaseInfo, ReleaseMetadata};
 use crate::{id, id::Id, ReleaseData};
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, Vector};
-use near_sdk::BorshStorageKey;
+use near_sdk::collections::{LookupMap, TreeMap, UnorderedMap, Vector};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId, BorshStorageKey, IntoStorageKey};
 
 #[derive(BorshSerialize, BorshStorageKey)]
 pub enum StorageKey {
     BlobData = 0x1,
     StatusList = 0x2,
     YankedList = 0x3,
+    RoleMembers = 0x4,
+    Proposals = 0x5,
+    ProposalBlobs = 0x6,
+    UploadSessions = 0x7,
+    UploadChunks = 0x8,
+    Tombstones = 0x9,
+    IdIndex = 0xA,
+    VersionIndex = 0xB,
+    ChecksumIndex = 0xC,
+    Channels = 0xD,
+    Packages = 0xE,
+    PackageData = 0xF,
+    PushRecords = 0x10,
+    Metadata = 0x11,
+    DeploymentTargets = 0x12,
+    DeploymentReleases = 0x13,
+    Signers = 0x14,
+    Signatures = 0x15,
+    EthSigners = 0x16,
+    EthAttestations = 0x17,
+    YankedIndex = 0x18,
+    DeploymentHistory = 0x19,
+    StableIndex = 0x1A,
+}
+
+/// Prefix of a collection of a package, keeping it apart from the
+/// collections of other packages.
+#[derive(BorshSerialize, BorshStorageKey)]
+struct PackageKey {
+    /// Always [`StorageKey::PackageData`].
+    prefix: StorageKey,
+    package: String,
+    key: StorageKey,
+}
+
+/// Maximum length of a channel name.
+pub const MAX_CHANNEL_LEN: usize = 32;
+
+/// Estimated number of bytes used by a release besides its data and metadata.
+///
+/// Covers the storage records of the data, the status, the push record and
+/// the indexes, or of
+/// the proposal holding the release in the proposal mode, as well as the
+/// record of a new package.
+pub const RELEASE_OVERHEAD: u64 = 2560;
+
+/// A record left in place of the purged release data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct Tombstone {
+    pub id: Id,
+    /// Size of the purged data in bytes.
+    pub size: u64,
+    /// Block timestamp in nanoseconds when the data was purged.
+    pub purged_at: U64,
+    pub purged_by: AccountId,
+}
+
+/// A named line of releases, such as `stable` or `beta`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Channel {
+    pub name: String,
+    /// The most recently promoted release which is still released.
+    pub latest: Option<Id>,
+}
+
+/// Defines how the latest release is determined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LatestMode {
+    /// The latest release is set explicitly.
+    Pinned,
+    /// The latest release is the highest released version which is not
+    /// a pre-release.
+    Computed,
 }
 
 /// Wrapper over NEAR `LookupMap` to insert, get and remove ids to data.
@@ -17,7 +93,87 @@ pub struct ReleaseStorage {
     releases: LookupMap<Id, ReleaseData>,
     status_list: Vector<IdStatus>,
     yanked_list: Vector<Id>,
+    tombstones: LookupMap<Id, Tombstone>,
     latest: Option<Id>,
+    unique_checksums: bool,
+    /// Positions of the releases in the status list.
+    index: LookupMap<Id, u64>,
+    /// Versions mapped to the releases stored under them, ordered by the
+    /// version precedence.
+    versions: TreeMap<Version, Id>,
+    /// Checksums mapped to the releases stored with them.
+    checksums: LookupMap<Checksum, Vec<Id>>,
+    /// Channels mapped to the releases promoted to them, the most recent
+    /// one last.
+    channels: UnorderedMap<String, Vec<Id>>,
+    push_records: LookupMap<Id, PushRecord>,
+    /// Metadata of the releases, omitted if empty.
+    metadata: LookupMap<Id, ReleaseMetadata>,
+    /// Positions of the yanked releases in the yanked list.
+    yanked_index: LookupMap<Id, u64>,
+    /// Versions of the released releases which are not pre-releases, so
+    /// that the highest one is found without walking the yanked ones.
+    stable: TreeMap<Version, Id>,
+}
+
+/// Layout of the release storage before the indexes were added.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyReleaseStorage {
+    releases: LookupMap<LegacyId, ReleaseData>,
+    status_list: Vector<LegacyIdStatus>,
+    yanked_list: Vector<LegacyId>,
+    latest: Option<LegacyId>,
+}
+
+impl LegacyReleaseStorage {
+    /// Builds the indexes of the stored releases. The stored Ids are kept in
+    /// place, since the legacy ones are serialized the same way as the Ids
+    /// without pre-release and build metadata. A yanked latest release isn't
+    /// kept pinned, since the legacy storage didn't unpin it on yank.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a stored major version doesn't fit into the
+    /// current encoding of versions.
+    pub fn migrate(self) -> Result<ReleaseStorage, error::StorageError> {
+        let mut storage = ReleaseStorage {
+            releases: reinterpret(&self.releases),
+            status_list: reinterpret(&self.status_list),
+            yanked_list: reinterpret(&self.yanked_list),
+            ..ReleaseStorage::default()
+        };
+        for i in 0..self.status_list.len() {
+            if let Some(id_status) = self.status_list.get(i) {
+                let id = convert_legacy(id_status.id)?;
+                storage.add_to_indexes(&id, i);
+                storage.update_stable(&IdStatus {
+                    id,
+                    status: id_status.status,
+                });
+            }
+        }
+        storage.index_yanked();
+        if let Some(latest) = self.latest.map(convert_legacy).transpose()? {
+            // Fails if the latest release is yanked.
+            storage.pin_latest(latest).ok();
+        }
+        Ok(storage)
+    }
+}
+
+fn convert_legacy(id: LegacyId) -> Result<Id, error::StorageError> {
+    let version = format!("v{}.{}.{}", id.major, id.minor, id.patch);
+    Id::try_from(id).map_err(|_| error::StorageError::LegacyVersion(version))
+}
+
+/// Reinterprets a collection, which holds only its storage prefix and length,
+/// as a collection of elements serialized the same way.
+fn reinterpret<T: BorshSerialize, U: BorshDeserialize>(collection: &T) -> U {
+    collection
+        .try_to_vec()
+        .ok()
+        .and_then(|bytes| U::try_from_slice(&bytes).ok())
+        .unwrap_or_else(|| env::panic_str("Failed to reinterpret a collection"))
 }
 
 #[allow(dead_code)]
@@ -27,40 +183,261 @@ impl ReleaseStorage {
         Self::default()
     }
 
-    pub fn insert(&mut self, id: Id, code: &ReleaseData, latest: bool) {
+    /// Creates the storage of a package, which collections are prefixed by
+    /// the package name.
+    #[must_use]
+    pub fn for_package(package: &str) -> Self {
+        Self::with_keys(Some(package))
+    }
+
+    fn with_keys(package: Option<&str>) -> Self {
+        let key = |key| storage_key(package, key);
+        Self {
+            releases: LookupMap::new(key(StorageKey::BlobData)),
+            status_list: Vector::new(key(StorageKey::StatusList)),
+            yanked_list: Vector::new(key(StorageKey::YankedList)),
+            tombstones: LookupMap::new(key(StorageKey::Tombstones)),
+            latest: None,
+            unique_checksums: false,
+            index: LookupMap::new(key(StorageKey::IdIndex)),
+            versions: TreeMap::new(key(StorageKey::VersionIndex)),
+            checksums: LookupMap::new(key(StorageKey::ChecksumIndex)),
+            channels: UnorderedMap::new(key(StorageKey::Channels)),
+            push_records: LookupMap::new(key(StorageKey::PushRecords)),
+            metadata: LookupMap::new(key(StorageKey::Metadata)),
+            yanked_index: LookupMap::new(key(StorageKey::YankedIndex)),
+            stable: TreeMap::new(key(StorageKey::StableIndex)),
+        }
+    }
+
+    /// Checks whether the release can be inserted. Returns `false` if the
+    /// identical release is already stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is already released with different
+    /// data, if the release was yanked, or if the data is already released
+    /// under another version while checksums must be unique.
+    pub fn check(&self, id: &Id) -> Result<bool, error::StorageError> {
+        if let Some((_, id_status)) = self.find(id) {
+            return match id_status.status {
+                id::Status::Released => Ok(false),
+                id::Status::Yanked | id::Status::Purged => {
+                    Err(error::StorageError::Yanked(id.to_string()))
+                }
+            };
+        }
+        // Versions differing only in build metadata have the same precedence.
+        if self.versions.contains_key(&id.version.without_build()) {
+            return Err(error::StorageError::DuplicateVersion(
+                id.version.to_string(),
+            ));
+        }
+        if self.unique_checksums {
+            if let Some(other) = self
+                .checksums
+                .get(&id.checksum)
+                .and_then(|ids| ids.into_iter().next())
+            {
+                return Err(error::StorageError::DuplicateChecksum(other.to_string()));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Inserts the release. Returns `false` and leaves the storage untouched
+    /// if the identical release is already stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release violates the rules described in
+    /// [`ReleaseStorage::check`].
+    pub fn insert(
+        &mut self,
+        id: Id,
+        code: &ReleaseData,
+        latest: bool,
+    ) -> Result<bool, error::StorageError> {
+        if !self.check(&id)? {
+            return Ok(false);
+        }
         self.releases.insert(&id, code);
         let id_status = IdStatus {
             id: id.clone(),
             status: id::Status::Released,
         };
         self.status_list.push(&id_status);
+        self.add_to_indexes(&id_status.id, self.status_list.len() - 1);
+        self.update_stable(&id_status);
         if latest {
             self.latest = Some(id);
         }
+        Ok(true)
+    }
+
+    /// Sets whether the same data may be released under several versions.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn set_unique_checksums(&mut self, unique_checksums: bool) {
+        self.unique_checksums = unique_checksums;
+    }
+
+    #[must_use]
+    pub const fn unique_checksums(&self) -> bool {
+        self.unique_checksums
     }
 
+    /// Yanks the release. Its data stays readable until it's purged.
     pub fn remove(&mut self, id: &Id) -> Option<IdStatus> {
-        self.releases.remove(id);
-
-        let mut i = 0;
-        let mut found = false;
-        for id_status in self.status_list.iter() {
-            if id_status.id == id.clone() {
-                found = true;
-                break;
-            }
-            i += 1;
+        let (i, mut id_status) = self.find(id)?;
+        if id_status.status != id::Status::Released {
+            return Some(id_status);
         }
-        if !found {
-            return None;
+        id_status.status = id::Status::Yanked;
+        self.status_list.replace(i, &id_status);
+        self.yanked_list.push(id);
+        self.yanked_index.insert(id, &(self.yanked_list.len() - 1));
+        self.update_stable(&id_status);
+        // A yanked release can't stay the latest one.
+        if self.latest.as_ref() == Some(id) {
+            self.latest = None;
         }
-        let id_status = IdStatus {
+        Some(id_status)
+    }
+
+    /// Checks whether the release can be purged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release isn't stored, isn't yanked or is
+    /// already purged.
+    pub fn check_purge(&self, id: &Id) -> Result<(), error::StorageError> {
+        match self.find(id) {
+            None => Err(error::StorageError::NotFound(id.to_string())),
+            Some((_, id_status)) => match id_status.status {
+                id::Status::Released => Err(error::StorageError::NotYanked(id.to_string())),
+                id::Status::Purged => Err(error::StorageError::Purged(id.to_string())),
+                id::Status::Yanked => Ok(()),
+            },
+        }
+    }
+
+    /// Removes the data of a yanked release, leaving a tombstone in its place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release can't be purged, as described in
+    /// [`ReleaseStorage::check_purge`].
+    pub fn purge(
+        &mut self,
+        id: &Id,
+        purged_at: u64,
+        purged_by: AccountId,
+    ) -> Result<Tombstone, error::StorageError> {
+        self.check_purge(id)?;
+        let Some((i, mut id_status)) = self.find(id) else {
+            return Err(error::StorageError::NotFound(id.to_string()));
+        };
+        let size = self
+            .releases
+            .remove(id)
+            .map_or(0, |code| code.0.len() as u64);
+        let tombstone = Tombstone {
             id: id.clone(),
-            status: id::Status::Yanked,
+            size,
+            purged_at: purged_at.into(),
+            purged_by,
         };
+        self.tombstones.insert(id, &tombstone);
+        id_status.status = id::Status::Purged;
         self.status_list.replace(i, &id_status);
-        self.yanked_list.push(id);
-        Some(id_status)
+        Ok(tombstone)
+    }
+
+    #[must_use]
+    pub fn get_tombstone(&self, id: &Id) -> Option<Tombstone> {
+        self.tombstones.get(id)
+    }
+
+    /// Restores a yanked release. The release data must be provided if it
+    /// was purged. The most recently yanked release takes the place of the
+    /// restored one in the yanked list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release isn't stored, isn't yanked, or if its
+    /// data is missing.
+    pub fn restore(
+        &mut self,
+        id: &Id,
+        code: Option<&ReleaseData>,
+    ) -> Result<IdStatus, error::StorageError> {
+        let Some((i, mut id_status)) = self.find(id) else {
+            return Err(error::StorageError::NotFound(id.to_string()));
+        };
+        if id_status.status == id::Status::Released {
+            return Err(error::StorageError::NotYanked(id.to_string()));
+        }
+        if !self.releases.contains_key(id) {
+            let Some(code) = code else {
+                return Err(error::StorageError::MissingData(id.to_string()));
+            };
+            self.releases.insert(id, code);
+        }
+        self.tombstones.remove(id);
+
+        id_status.status = id::Status::Released;
+        self.status_list.replace(i, &id_status);
+        self.update_stable(&id_status);
+        if let Some(i) = self.yanked_index.remove(id) {
+            self.yanked_list.swap_remove(i);
+            if let Some(moved) = self.yanked_list.get(i) {
+                self.yanked_index.insert(&moved, &i);
+            }
+        }
+        Ok(id_status)
+    }
+
+    /// Records how the release was pushed, along with its metadata.
+    pub fn record_push(&mut self, id: &Id, record: &PushRecord, metadata: &ReleaseMetadata) {
+        self.push_records.insert(id, record);
+        self.put_metadata(id, metadata);
+    }
+
+    /// Replaces the metadata of the release.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release isn't stored.
+    pub fn set_metadata(
+        &mut self,
+        id: &Id,
+        metadata: &ReleaseMetadata,
+    ) -> Result<(), error::StorageError> {
+        if self.find(id).is_none() {
+            return Err(error::StorageError::NotFound(id.to_string()));
+        }
+        self.put_metadata(id, metadata);
+        Ok(())
+    }
+
+    fn put_metadata(&mut self, id: &Id, metadata: &ReleaseMetadata) {
+        if metadata == &ReleaseMetadata::default() {
+            self.metadata.remove(id);
+        } else {
+            self.metadata.insert(id, metadata);
+        }
+    }
+
+    /// Returns everything known about the release, except its data.
+    #[must_use]
+    pub fn info(&self, id: &Id) -> Option<ReleaseInfo> {
+        let (_, id_status) = self.find(id)?;
+        Some(ReleaseInfo {
+            id: id_status.id,
+            status: id_status.status,
+            pushed: self.push_records.get(id),
+            metadata: self.metadata.get(id).unwrap_or_default(),
+        })
     }
 
     #[must_use]
@@ -68,37 +445,540 @@ impl ReleaseStorage {
         self.releases.get(id)
     }
 
-    #[allow(clippy::missing_const_for_fn)]
     #[must_use]
-    pub fn list(self) -> Vec<IdStatus> {
-        self.status_list.to_vec()
+    pub fn list(&self, from_index: u64, limit: u64, reverse: bool) -> Vec<IdStatus> {
+        page(&self.status_list, from_index, limit, reverse)
+    }
+
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.status_list.len()
     }
 
-    #[allow(clippy::missing_const_for_fn)]
     #[must_use]
-    pub fn yanks(self) -> Vec<Id> {
-        self.yanked_list.to_vec()
+    pub fn yanks(&self, from_index: u64, limit: u64, reverse: bool) -> Vec<Id> {
+        page(&self.yanked_list, from_index, limit, reverse)
     }
 
+    #[must_use]
+    pub fn yank_count(&self) -> u64 {
+        self.yanked_list.len()
+    }
+
+    /// Returns the pinned latest release, or the highest released version
+    /// which is not a pre-release if nothing is pinned.
     #[must_use]
     pub fn latest(&self) -> Option<Id> {
-        self.latest.clone()
+        self.latest.clone().or_else(|| self.highest_release())
+    }
+
+    #[must_use]
+    pub const fn latest_mode(&self) -> LatestMode {
+        if self.latest.is_some() {
+            LatestMode::Pinned
+        } else {
+            LatestMode::Computed
+        }
+    }
+
+    /// Pins the release as the latest one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release isn't stored or is yanked.
+    pub fn pin_latest(&mut self, id: Id) -> Result<(), error::StorageError> {
+        match self.get_status(id.clone()) {
+            None => return Err(error::StorageError::NotFound(id.to_string())),
+            Some(IdStatus {
+                status: id::Status::Yanked | id::Status::Purged,
+                ..
+            }) => return Err(error::StorageError::Yanked(id.to_string())),
+            Some(_) => {}
+        }
+        self.latest = Some(id);
+        Ok(())
+    }
+
+    /// Unpins the latest release, so that it's computed from the stored ones.
+    pub fn unpin_latest(&mut self) {
+        self.latest = None;
+    }
+
+    /// Promotes the release to the channel, creating the channel if needed.
+    /// Promoting a release again moves it to the top of the channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel name is invalid, or if the release
+    /// isn't stored or is yanked.
+    pub fn promote(&mut self, id: &Id, channel: &str) -> Result<(), error::StorageError> {
+        check_channel(channel)?;
+        match self.find(id) {
+            None => return Err(error::StorageError::NotFound(id.to_string())),
+            Some((_, id_status)) if id_status.status != id::Status::Released => {
+                return Err(error::StorageError::Yanked(id.to_string()));
+            }
+            Some(_) => {}
+        }
+        let channel = channel.to_string();
+        let mut history = self.channels.get(&channel).unwrap_or_default();
+        history.retain(|promoted| promoted != id);
+        history.push(id.clone());
+        self.channels.insert(&channel, &history);
+        Ok(())
+    }
+
+    /// Returns the latest release of the channel. If the most recently
+    /// promoted release is yanked, the channel falls back to the release
+    /// promoted before it.
+    #[must_use]
+    pub fn latest_in(&self, channel: &str) -> Option<Id> {
+        let history = self.channels.get(&channel.to_string())?;
+        self.latest_released(history)
+    }
+
+    #[must_use]
+    pub fn channels(&self) -> Vec<Channel> {
+        self.channels
+            .iter()
+            .map(|(name, history)| Channel {
+                name,
+                latest: self.latest_released(history),
+            })
+            .collect()
+    }
+
+    fn latest_released(&self, history: Vec<Id>) -> Option<Id> {
+        history.into_iter().rev().find(|id| {
+            matches!(self.find(id), Some((_, id_status)) if id_status.status == id::Status::Released)
+        })
+    }
+
+    fn highest_release(&self) -> Option<Id> {
+        self.stable.iter_rev().next().map(|(_, id)| id)
+    }
+
+    /// Returns the status of the release stored under the version. Build
+    /// metadata is ignored.
+    #[must_use]
+    pub fn get_by_version(&self, version: &Version) -> Option<IdStatus> {
+        let id = self.versions.get(&version.without_build())?;
+        self.find(&id).map(|(_, id_status)| id_status)
+    }
+
+    /// Returns the statuses of the releases stored with the checksum.
+    #[must_use]
+    pub fn get_by_checksum(&self, checksum: &Checksum) -> Vec<IdStatus> {
+        self.checksums
+            .get(checksum)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| self.find(id).map(|(_, id_status)| id_status))
+            .collect()
+    }
+
+    /// Returns the statuses of the releases which versions match the
+    /// requirement and which have the given status, if any, in the version
+    /// precedence order. Only the highest match is returned if `max_only`
+    /// is set.
+    #[must_use]
+    pub fn matching(
+        &self,
+        req: &semver::VersionReq,
+        status: Option<&id::Status>,
+        max_only: bool,
+    ) -> Vec<IdStatus> {
+        let matches = |version: &Version, id: &Id| {
+            if !version.matches(req) {
+                return None;
+            }
+            self.find(id)
+                .map(|(_, id_status)| id_status)
+                .filter(|id_status| status.is_none() || status == Some(&id_status.status))
+        };
+        if max_only {
+            return self
+                .versions
+                .iter_rev()
+                .find_map(|(version, id)| matches(&version, &id))
+                .into_iter()
+                .collect();
+        }
+        self.versions
+            .iter()
+            .filter_map(|(version, id)| matches(&version, &id))
+            .collect()
     }
 
     #[allow(clippy::needless_pass_by_value)]
     #[must_use]
     pub fn get_status(&self, id: Id) -> Option<IdStatus> {
-        self.status_list.iter().find(|id_status| id_status.id == id)
+        self.find(&id).map(|(_, id_status)| id_status)
+    }
+
+    /// Returns the position of the release in the status list along with
+    /// its status.
+    fn find(&self, id: &Id) -> Option<(u64, IdStatus)> {
+        let i = self.index.get(id)?;
+        self.status_list.get(i).map(|id_status| (i, id_status))
+    }
+
+    fn index_yanked(&mut self) {
+        for i in 0..self.yanked_list.len() {
+            if let Some(id) = self.yanked_list.get(i) {
+                self.yanked_index.insert(&id, &i);
+            }
+        }
+    }
+
+    /// Adds the release to the stable index if it's released and isn't a
+    /// pre-release, or removes it from the index otherwise.
+    fn update_stable(&mut self, id_status: &IdStatus) {
+        let version = id_status.id.version.without_build();
+        if id_status.status == id::Status::Released && !version.is_prerelease() {
+            self.stable.insert(&version, &id_status.id);
+        } else {
+            self.stable.remove(&version);
+        }
+    }
+
+    fn add_to_indexes(&mut self, id: &Id, i: u64) {
+        self.index.insert(id, &i);
+        self.versions.insert(&id.version.without_build(), id);
+        let mut ids = self.checksums.get(&id.checksum).unwrap_or_default();
+        ids.push(id.clone());
+        self.checksums.insert(&id.checksum, &ids);
+    }
+}
+
+/// Returns the key of the collection of the package, or of the default
+/// package if no package is given.
+fn storage_key(package: Option<&str>, key: StorageKey) -> Vec<u8> {
+    match package {
+        None => key.into_storage_key(),
+        Some(package) => PackageKey {
+            prefix: StorageKey::PackageData,
+            package: package.to_string(),
+            key,
+        }
+        .into_storage_key(),
+    }
+}
+
+/// Returns at most `limit` items starting from `from_index`. The items are
+/// counted from the end of the vector if `reverse` is set.
+fn page<T>(vector: &Vector<T>, from_index: u64, limit: u64, reverse: bool) -> Vec<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    let len = vector.len();
+    let to_index = from_index.saturating_add(limit).min(len);
+    (from_index.min(len)..to_index)
+        .filter_map(|i| vector.get(if reverse { len - 1 - i } else { i }))
+        .collect()
+}
+
+/// Checks that the channel name is made of lowercase ASCII letters, digits
+/// and `-`, and isn't longer than [`MAX_CHANNEL_LEN`].
+///
+/// # Errors
+///
+/// Returns an error if the channel name is invalid.
+pub fn check_channel(channel: &str) -> Result<(), error::StorageError> {
+    let is_valid = !channel.is_empty()
+        && channel.len() <= MAX_CHANNEL_LEN
+        && channel
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if is_valid {
+        Ok(())
+    } else {
+        Err(error::StorageError::InvalidChannel(channel.to_string()))
     }
 }
 
 impl Default for ReleaseStorage {
+    /// Creates the storage of the default package, which collections keep
+    /// the prefixes used before packages were supported.
     fn default() -> Self {
-        Self {
+        Self::with_keys(None)
+    }
+}
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum StorageError {
+        #[error("version `{0}` is already released with different data")]
+        DuplicateVersion(String),
+        #[error("the data is already released as `{0}`")]
+        DuplicateChecksum(String),
+        #[error("release `{0}` is yanked")]
+        Yanked(String),
+        #[error("release `{0}` is not found")]
+        NotFound(String),
+        #[error("release `{0}` is not yanked")]
+        NotYanked(String),
+        #[error("data of release `{0}` is already purged")]
+        Purged(String),
+        #[error("data of release `{0}` is missing and must be provided")]
+        MissingData(String),
+        #[error("invalid channel name `{0}`, expected lowercase letters, digits and `-`")]
+        InvalidChannel(String),
+        #[error("legacy version `{0}` has a major version too large to be migrated")]
+        LegacyVersion(String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(version: &str, checksum: u8) -> Id {
+        Id::new(
+            Version::try_from(version).unwrap(),
+            Checksum(vec![checksum; 32]),
+        )
+    }
+
+    fn legacy_id(major: u32, patch: u32, checksum: u8) -> LegacyId {
+        LegacyId {
+            major,
+            minor: 0,
+            patch,
+            checksum: Checksum(vec![checksum; 32]),
+        }
+    }
+
+    fn legacy_storage(ids: &[LegacyId]) -> LegacyReleaseStorage {
+        let mut legacy = LegacyReleaseStorage {
             releases: LookupMap::new(StorageKey::BlobData),
             status_list: Vector::new(StorageKey::StatusList),
             yanked_list: Vector::new(StorageKey::YankedList),
             latest: None,
+        };
+        for id in ids {
+            legacy.releases.insert(id, &ReleaseData(vec![1, 2, 3]));
+            legacy.status_list.push(&LegacyIdStatus {
+                id: id.clone(),
+                status: id::Status::Released,
+            });
         }
+        legacy
+    }
+
+    #[test]
+    fn test_migrate_builds_indexes() {
+        let legacy = legacy_storage(&[legacy_id(1, 0, 0), legacy_id(1, 1, 1)]);
+
+        let mut storage = legacy.migrate().unwrap();
+        assert_eq!(storage.get(&id("v1.0.0", 0)).unwrap().0, vec![1, 2, 3]);
+        let released = id("v1.0.1", 1);
+        assert!(storage.get_status(released.clone()).is_some());
+        assert!(!storage.check(&released).unwrap());
+        assert!(matches!(
+            storage.check(&id("v1.0.0", 9)),
+            Err(error::StorageError::DuplicateVersion(_))
+        ));
+
+        storage.set_unique_checksums(true);
+        assert!(matches!(
+            storage.check(&id("v1.0.2", 1)),
+            Err(error::StorageError::DuplicateChecksum(_))
+        ));
+
+        let id_status = storage.remove(&released).unwrap();
+        assert_eq!(id_status.status, id::Status::Yanked);
+        assert_eq!(storage.yanks(0, 10, false), vec![released]);
+        assert!(storage.check(&id("v1.0.2", 2)).unwrap());
+    }
+
+    #[test]
+    fn test_build_metadata_is_ignored() {
+        let mut storage = ReleaseStorage::new();
+        let built = id("v1.0.0+a", 1);
+        storage
+            .insert(built.clone(), &ReleaseData(vec![]), false)
+            .unwrap();
+
+        assert!(matches!(
+            storage.check(&id("v1.0.0+b", 2)),
+            Err(error::StorageError::DuplicateVersion(_))
+        ));
+        assert!(matches!(
+            storage.check(&id("v1.0.0", 2)),
+            Err(error::StorageError::DuplicateVersion(_))
+        ));
+        for version in ["v1.0.0", "v1.0.0+b"] {
+            let version = Version::try_from(version).unwrap();
+            assert_eq!(storage.get_by_version(&version).unwrap().id, built);
+        }
+    }
+
+    #[test]
+    fn test_restore_moves_last_yanked_release() {
+        let mut storage = ReleaseStorage::new();
+        let ids: Vec<Id> = (0..3u8).map(|i| id(&format!("v1.0.{i}"), i)).collect();
+        for id in &ids {
+            storage
+                .insert(id.clone(), &ReleaseData(vec![]), false)
+                .unwrap();
+            storage.remove(id);
+        }
+
+        storage.restore(&ids[0], None).unwrap();
+        assert_eq!(
+            storage.yanks(0, 10, false),
+            vec![ids[2].clone(), ids[1].clone()]
+        );
+        storage.restore(&ids[1], None).unwrap();
+        assert_eq!(storage.yanks(0, 10, false), vec![ids[2].clone()]);
+        storage.remove(&ids[0]);
+        storage.restore(&ids[2], None).unwrap();
+        assert_eq!(storage.yanks(0, 10, false), vec![ids[0].clone()]);
+    }
+
+    #[test]
+    fn test_latest_is_highest_stable_release() {
+        let mut storage = ReleaseStorage::new();
+        let ids: Vec<Id> = ["v1.0.0", "v1.1.0", "v2.0.0-rc.1"]
+            .iter()
+            .zip(0..)
+            .map(|(version, i)| id(version, i))
+            .collect();
+        for id in &ids {
+            storage
+                .insert(id.clone(), &ReleaseData(vec![]), false)
+                .unwrap();
+        }
+        assert_eq!(storage.latest(), Some(ids[1].clone()));
+
+        storage.remove(&ids[1]);
+        assert_eq!(storage.latest(), Some(ids[0].clone()));
+        storage.remove(&ids[0]);
+        assert_eq!(storage.latest(), None);
+        storage.restore(&ids[1], None).unwrap();
+        assert_eq!(storage.latest(), Some(ids[1].clone()));
+    }
+
+    #[test]
+    fn test_migrate_unpins_yanked_latest() {
+        // The legacy storage kept yanked releases pinned.
+        let mut legacy = legacy_storage(&[legacy_id(1, 0, 0), legacy_id(1, 1, 1)]);
+        legacy.status_list.replace(
+            1,
+            &LegacyIdStatus {
+                id: legacy_id(1, 1, 1),
+                status: id::Status::Yanked,
+            },
+        );
+        legacy.yanked_list.push(&legacy_id(1, 1, 1));
+        legacy.latest = Some(legacy_id(1, 1, 1));
+        let storage = legacy.migrate().unwrap();
+        assert_eq!(storage.latest(), Some(id("v1.0.0", 0)));
+        assert_eq!(storage.latest_mode(), LatestMode::Computed);
+    }
+
+    #[test]
+    fn test_migrate_rejects_large_major_versions() {
+        for major in [1 << 31, u32::MAX] {
+            let legacy = legacy_storage(&[legacy_id(1, 0, 0), legacy_id(major, 0, 1)]);
+            assert!(matches!(
+                legacy.migrate(),
+                Err(error::StorageError::LegacyVersion(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_matching_versions() {
+        let mut storage = ReleaseStorage::new();
+        for (i, version) in ["v2.0.0", "v1.5.0", "v1.4.2", "v2.1.0-rc.1", "v1.6.0"]
+            .into_iter()
+            .enumerate()
+        {
+            let id = id(version, u8::try_from(i).unwrap());
+            storage.insert(id, &ReleaseData(vec![]), false).unwrap();
+        }
+        storage.remove(&id("v1.5.0", 1));
+        let versions = |req: &str, status: Option<&id::Status>, max_only: bool| {
+            let req = semver::VersionReq::parse(req).unwrap();
+            storage
+                .matching(&req, status, max_only)
+                .into_iter()
+                .map(|id_status| id_status.id.version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(versions(">=1.4, <1.6", None, false), ["v1.4.2", "v1.5.0"]);
+        assert_eq!(
+            versions(">=1.4, <1.6", Some(&id::Status::Released), false),
+            ["v1.4.2"]
+        );
+        assert_eq!(versions("^1", None, true), ["v1.6.0"]);
+        assert_eq!(versions("^2", None, false), ["v2.0.0"]);
+        assert_eq!(versions(">=2.1.0-rc.1", None, false), ["v2.1.0-rc.1"]);
+        assert_eq!(versions("^3", None, true), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_channel_fallback() {
+        let mut storage = ReleaseStorage::new();
+        let ids: Vec<Id> = (0..3u8).map(|i| id(&format!("v1.0.{i}"), i)).collect();
+        for id in &ids {
+            storage
+                .insert(id.clone(), &ReleaseData(vec![]), false)
+                .unwrap();
+        }
+        storage.promote(&ids[0], "stable").unwrap();
+        storage.promote(&ids[1], "stable").unwrap();
+        storage.promote(&ids[2], "beta").unwrap();
+        assert_eq!(storage.latest_in("stable"), Some(ids[1].clone()));
+        assert_eq!(storage.latest_in("beta"), Some(ids[2].clone()));
+        assert_eq!(storage.latest_in("nightly"), None);
+
+        //== Yanking the latest falls back to the previously promoted release
+        storage.remove(&ids[1]);
+        assert_eq!(storage.latest_in("stable"), Some(ids[0].clone()));
+        storage.remove(&ids[2]);
+        assert_eq!(storage.latest_in("beta"), None);
+        assert!(matches!(
+            storage.promote(&ids[2], "stable"),
+            Err(error::StorageError::Yanked(_))
+        ));
+
+        //== Promoting again moves the release to the top
+        storage.promote(&ids[0], "beta").unwrap();
+        storage.restore(&ids[2], None).unwrap();
+        assert_eq!(storage.latest_in("beta"), Some(ids[0].clone()));
+
+        assert!(matches!(
+            storage.promote(&ids[0], "Stable"),
+            Err(error::StorageError::InvalidChannel(_))
+        ));
+        assert_eq!(storage.channels().len(), 2);
+    }
+
+    #[test]
+    fn test_package_storages_are_separate() {
+        let mut default = ReleaseStorage::new();
+        let mut engine = ReleaseStorage::for_package("engine");
+        let id = id("v1.0.0", 1);
+        default
+            .insert(id.clone(), &ReleaseData(vec![1]), false)
+            .unwrap();
+
+        let packaged = id.clone().in_package(Some("engine".to_string()));
+        assert!(engine.get_status(packaged.clone()).is_none());
+        assert!(engine.get_by_version(&packaged.version).is_none());
+        engine
+            .insert(packaged.clone(), &ReleaseData(vec![2]), false)
+            .unwrap();
+
+        assert_eq!(default.count(), 1);
+        assert_eq!(engine.count(), 1);
+        assert_eq!(default.get(&id).unwrap().0, vec![1]);
+        assert_eq!(engine.get(&packaged).unwrap().0, vec![2]);
     }
 }
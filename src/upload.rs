@@ -0,0 +1,102 @@
+use crate::id::Id;
+use crate::storage::StorageKey;
+use crate::ReleaseData;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::serde::Serialize;
+use near_sdk::AccountId;
+
+/// Maximum length of release data in bytes. The data is stored with a 4-byte
+/// length prefix, which must fit into the 4 MiB limit of a storage value in
+/// NEAR.
+pub const MAX_RELEASE_LEN: u32 = 4 * 1024 * 1024 - 4;
+
+/// An upload of release data split into several transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, BorshSerialize, BorshDeserialize)]
+pub struct UploadSession {
+    pub id: u64,
+    /// The account which began the upload.
+    pub uploader: AccountId,
+    /// The Id of the release built from the expected checksum.
+    pub release_id: Id,
+    /// Expected length of the release data in bytes.
+    pub total_len: u32,
+    /// Number of bytes received so far.
+    pub received_len: u32,
+    /// Number of chunks received so far.
+    pub chunks: u32,
+}
+
+/// Wrapper over NEAR `UnorderedMap` to keep upload sessions and the chunks
+/// received in them.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct UploadStorage {
+    sessions: UnorderedMap<u64, UploadSession>,
+    chunks: LookupMap<(u64, u32), Vec<u8>>,
+    next_id: u64,
+}
+
+impl UploadStorage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&mut self, uploader: AccountId, release_id: Id, total_len: u32) -> UploadSession {
+        let session = UploadSession {
+            id: self.next_id,
+            uploader,
+            release_id,
+            total_len,
+            received_len: 0,
+            chunks: 0,
+        };
+        self.next_id += 1;
+        self.sessions.insert(&session.id, &session);
+        session
+    }
+
+    /// Appends the chunk to the end of the data received in the session.
+    /// The caller must ensure the chunk fits into the expected length.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn append(&mut self, session: &mut UploadSession, bytes: &[u8]) {
+        session.received_len += bytes.len() as u32;
+        self.chunks
+            .insert(&(session.id, session.chunks), &bytes.to_vec());
+        session.chunks += 1;
+        self.sessions.insert(&session.id, session);
+    }
+
+    /// Removes the session along with its chunks, returning the data
+    /// received so far.
+    pub fn remove(&mut self, session: &UploadSession) -> ReleaseData {
+        let mut data = Vec::with_capacity(session.received_len as usize);
+        for i in 0..session.chunks {
+            if let Some(chunk) = self.chunks.remove(&(session.id, i)) {
+                data.extend(chunk);
+            }
+        }
+        self.sessions.remove(&session.id);
+        ReleaseData(data)
+    }
+
+    #[must_use]
+    pub fn get(&self, session_id: u64) -> Option<UploadSession> {
+        self.sessions.get(&session_id)
+    }
+
+    #[must_use]
+    pub fn list(&self) -> Vec<UploadSession> {
+        self.sessions.values().collect()
+    }
+}
+
+impl Default for UploadStorage {
+    fn default() -> Self {
+        Self {
+            sessions: UnorderedMap::new(StorageKey::UploadSessions),
+            chunks: LookupMap::new(StorageKey::UploadChunks),
+            next_id: 0,
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use crate::utils::{CustomId, CustomIdStatus, TestContract};
+use crate::utils::{CustomId, CustomIdStatus, CustomTombstone, TestContract};
 use aurora_release_repository::id::{Checksum, Id, Status, Version};
+use aurora_release_repository::roles::Role;
 use near_sdk::env::sha256;
 use near_sdk::json_types::Base64VecU8;
+use near_sdk::ONE_YOCTO;
+use serde_json::json;
 use workspaces::AccountId;
 
 mod utils;
@@ -261,3 +264,1671 @@ async fn test_owners() {
         .unwrap();
     assert_eq!(&owner, contract.contract.id());
 }
+
+#[tokio::test]
+async fn test_ownership_transfer() {
+    let contract = TestContract::new(None).await.unwrap();
+    let alice = contract.create_account("alice").await.unwrap();
+    let bob = contract.create_account("bob").await.unwrap();
+
+    assert_eq!(contract.get_pending_owner().await.unwrap(), None);
+
+    //== Only the owner may propose
+    let res = contract.propose_owner(&alice, alice.id()).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .propose_owner(contract.owner(), alice.id())
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    assert_eq!(
+        contract.get_pending_owner().await.unwrap().as_ref(),
+        Some(alice.id())
+    );
+
+    //== Only the pending owner may accept
+    let res = contract.accept_ownership(&bob).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract.accept_ownership(&alice).await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(&contract.get_owner().await.unwrap(), alice.id());
+    assert_eq!(contract.get_pending_owner().await.unwrap(), None);
+
+    //== The previous owner has lost access
+    let res = contract
+        .propose_owner(contract.owner(), bob.id())
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_cancel_ownership_transfer() {
+    let contract = TestContract::new(None).await.unwrap();
+    let alice = contract.create_account("alice").await.unwrap();
+
+    let res = contract
+        .cancel_ownership_transfer(contract.owner())
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .propose_owner(contract.owner(), alice.id())
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    let res = contract
+        .cancel_ownership_transfer(contract.owner())
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    assert_eq!(contract.get_pending_owner().await.unwrap(), None);
+
+    let res = contract.accept_ownership(&alice).await.unwrap();
+    assert!(res.is_failure());
+    assert_eq!(&contract.get_owner().await.unwrap(), contract.contract.id());
+}
+
+#[tokio::test]
+async fn test_roles() {
+    let contract = TestContract::new(None).await.unwrap();
+    let admin = contract.create_account("admin").await.unwrap();
+    let publisher = contract.create_account("publisher").await.unwrap();
+    let yanker = contract.create_account("yanker").await.unwrap();
+
+    //== The owner implicitly holds every role
+    assert!(contract
+        .has_role(contract.contract.id(), Role::Admin)
+        .await
+        .unwrap());
+    assert!(!contract.has_role(admin.id(), Role::Admin).await.unwrap());
+
+    //== Only admins grant roles
+    let res = contract
+        .grant_role(&admin, admin.id(), Role::Admin)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .grant_role(contract.owner(), admin.id(), Role::Admin)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    for (account, role) in [(&publisher, Role::Publisher), (&yanker, Role::Yanker)] {
+        let res = contract
+            .grant_role(&admin, account.id(), role)
+            .await
+            .unwrap();
+        assert!(res.is_success());
+    }
+    assert!(contract
+        .has_role(publisher.id(), Role::Publisher)
+        .await
+        .unwrap());
+    assert_eq!(
+        contract.list_role_members(Role::Yanker).await.unwrap(),
+        vec![yanker.id().clone()]
+    );
+
+    //== Each method checks its own role
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum);
+    let code = Base64VecU8(code_data);
+
+    let res = contract
+        .push_as(&yanker, version, &code, false, 6)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .push_as(&publisher, version, &code, false, 6)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    let res = contract.pull_as(&publisher, &id).await.unwrap();
+    assert!(res.is_failure());
+    let res = contract.pull_as(&yanker, &id).await.unwrap();
+    assert!(res.is_success());
+
+    //== Revoked roles no longer grant access
+    let res = contract
+        .revoke_role(&admin, publisher.id(), Role::Publisher)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    assert!(contract
+        .list_role_members(Role::Publisher)
+        .await
+        .unwrap()
+        .is_empty());
+    let res = contract
+        .push_as(&publisher, "v1.2.4", &code, false, 6)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_push_and_pull_with_approvals() {
+    let contract = TestContract::new(None).await.unwrap();
+    let alice = contract.create_account("alice").await.unwrap();
+    let bob = contract.create_account("bob").await.unwrap();
+    let carol = contract.create_account("carol").await.unwrap();
+
+    //== Threshold can't exceed the number of approvers
+    let res = contract
+        .set_approval_policy(&[alice.id(), bob.id()], 3, 3_600_000_000_000)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .set_approval_policy(&[alice.id(), bob.id(), carol.id()], 2, 3_600_000_000_000)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    //== Push only creates a proposal
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum);
+    let code = Base64VecU8(code_data);
+
+    let res = contract.push(version, &code, true, 10).await.unwrap();
+    assert!(res.is_success());
+    assert!(contract.list().await.unwrap().is_empty());
+    let proposals = contract.list_proposals().await.unwrap();
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(&proposals[0].proposer, contract.contract.id());
+    assert!(proposals[0].action.get("Push").is_some());
+    let proposal_id = proposals[0].id;
+
+    //== Only approvers approve, once each
+    let res = contract
+        .approve(contract.owner(), proposal_id)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract.approve(&alice, proposal_id).await.unwrap();
+    assert!(!res.json::<bool>().unwrap());
+    let res = contract.approve(&alice, proposal_id).await.unwrap();
+    assert!(res.is_failure());
+    assert!(contract.list().await.unwrap().is_empty());
+    let proposals = contract.list_proposals().await.unwrap();
+    assert_eq!(proposals[0].approvals, vec![alice.id().clone()]);
+
+    //== The threshold executes the push, charging the approver only for the
+    //== storage not paid by the proposal
+    let balance = bob.view_account().await.unwrap().balance;
+    let res = contract.approve(&bob, proposal_id).await.unwrap();
+    assert!(res.json::<bool>().unwrap());
+    let spent = balance - bob.view_account().await.unwrap().balance;
+    assert!(spent < contract.proposal_deposit().await.unwrap() / 2);
+    assert!(contract.list_proposals().await.unwrap().is_empty());
+    assert_eq!(contract.get_blob(&id).await.unwrap(), code);
+    let res: CustomId = contract.get_latest().await.unwrap();
+    assert_eq!(res.checksum, id.checksum.to_string());
+
+    //== Pull goes through a proposal as well
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Released);
+    let proposal_id = contract.list_proposals().await.unwrap()[0].id;
+    for approver in [&bob, &carol] {
+        let res = contract.approve(approver, proposal_id).await.unwrap();
+        assert!(res.is_success());
+    }
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Yanked);
+
+    //== So does restore
+    let res = contract.restore(&id, None, None).await.unwrap();
+    assert_eq!(res.json::<CustomIdStatus>().unwrap().status, Status::Yanked);
+    let proposals = contract.list_proposals().await.unwrap();
+    assert!(proposals[0].action.get("Restore").is_some());
+    for approver in [&alice, &carol] {
+        let res = contract.approve(approver, proposals[0].id).await.unwrap();
+        assert!(res.is_success());
+    }
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Released);
+
+    //== And purge, which is proposed only for a yanked release
+    let res = contract.purge(&id).await.unwrap();
+    assert!(res.is_failure());
+    assert!(contract.list_proposals().await.unwrap().is_empty());
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let proposal_id = contract.list_proposals().await.unwrap()[0].id;
+    for approver in [&alice, &bob] {
+        let res = contract.approve(approver, proposal_id).await.unwrap();
+        assert!(res.is_success());
+    }
+    let res = contract.purge(&id).await.unwrap();
+    assert!(res.json::<Option<CustomTombstone>>().unwrap().is_none());
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Yanked);
+    let proposals = contract.list_proposals().await.unwrap();
+    assert!(proposals[0].action.get("Purge").is_some());
+    for approver in [&alice, &bob] {
+        let res = contract.approve(approver, proposals[0].id).await.unwrap();
+        assert!(res.is_success());
+    }
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Purged);
+}
+
+#[tokio::test]
+async fn test_cancel_and_expire_proposals() {
+    let contract = TestContract::new(None).await.unwrap();
+    let alice = contract.create_account("alice").await.unwrap();
+    let bob = contract.create_account("bob").await.unwrap();
+
+    let res = contract
+        .set_approval_policy(&[alice.id(), bob.id()], 1, 3_600_000_000_000)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    let code = Base64VecU8(vec![100, 121, 31, 20, 0, 23, 32]);
+    let res = contract.push("v1.2.3", &code, false, 10).await.unwrap();
+    assert!(res.is_success());
+    let proposal_id = contract.list_proposals().await.unwrap()[0].id;
+
+    //== Only the proposer or admins cancel pending proposals
+    let res = contract.cancel_proposal(&alice, proposal_id).await.unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .cancel_proposal(contract.owner(), proposal_id)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    assert!(contract.list_proposals().await.unwrap().is_empty());
+    let res = contract.approve(&alice, proposal_id).await.unwrap();
+    assert!(res.is_failure());
+
+    //== Expired proposals aren't listed and can't be approved, but anyone
+    //== can clean them up
+    let res = contract
+        .set_approval_policy(&[alice.id(), bob.id()], 1, 1)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let policy_id = contract.list_proposals().await.unwrap()[0].id;
+    let res = contract.approve(&alice, policy_id).await.unwrap();
+    assert!(res.json::<bool>().unwrap());
+    let res = contract.push("v1.2.3", &code, false, 10).await.unwrap();
+    assert!(res.is_success());
+    assert!(contract.list_proposals().await.unwrap().is_empty());
+    let proposal_id = policy_id + 1;
+
+    let res = contract.approve(&alice, proposal_id).await.unwrap();
+    assert!(res.is_failure());
+    let res = contract.cancel_proposal(&bob, proposal_id).await.unwrap();
+    assert!(res.is_success());
+    assert!(contract.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_governance_with_approvals() {
+    let contract = TestContract::new(None).await.unwrap();
+    let alice = contract.create_account("alice").await.unwrap();
+    let bob = contract.create_account("bob").await.unwrap();
+    let carol = contract.create_account("carol").await.unwrap();
+
+    let res = contract
+        .set_approval_policy(&[alice.id(), bob.id()], 2, 3_600_000_000_000)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    //== Roles and the policy only change once the proposals are approved
+    let res = contract
+        .grant_role(contract.owner(), carol.id(), Role::Admin)
+        .await
+        .unwrap();
+    assert!(!res.json::<bool>().unwrap());
+    assert!(!contract.has_role(carol.id(), Role::Admin).await.unwrap());
+    //== Proposals must be paid for
+    let res = contract
+        .call_with("set_approval_policy", json!({ "policy": null }))
+        .await
+        .unwrap();
+    assert!(format!("{:?}", res.into_result().unwrap_err()).contains("insufficient deposit"));
+    let deposit = contract.proposal_deposit().await.unwrap();
+    let res = contract
+        .call_with_deposit("set_approval_policy", json!({ "policy": null }), deposit)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("get_approval_policy", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(res["threshold"], json!(2));
+
+    let proposals = contract.list_proposals().await.unwrap();
+    assert_eq!(proposals.len(), 2);
+    assert!(proposals[0].action.get("GrantRole").is_some());
+    assert!(proposals[1].action.get("SetApprovalPolicy").is_some());
+    for proposal in &proposals {
+        for approver in [&alice, &bob] {
+            let res = contract.approve(approver, proposal.id).await.unwrap();
+            assert!(res.is_success());
+        }
+    }
+    assert!(contract.has_role(carol.id(), Role::Admin).await.unwrap());
+    let res = contract
+        .view_with("get_approval_policy", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(null));
+}
+
+#[tokio::test]
+async fn test_chunked_upload_1mb() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum.clone());
+    let total_len = u32::try_from(code_data.len()).unwrap();
+
+    let res = contract
+        .begin_upload(version, total_len, &checksum.to_string())
+        .await
+        .unwrap();
+    let session_id = res.json::<u64>().unwrap();
+
+    let mut offset = 0;
+    for chunk in code_data.chunks(256 * 1024) {
+        //== Chunks must be appended in order
+        let res = contract
+            .append_chunk(session_id, offset + 1, &Base64VecU8(chunk.to_vec()))
+            .await
+            .unwrap();
+        assert!(res.is_failure());
+
+        let res = contract
+            .append_chunk(session_id, offset, &Base64VecU8(chunk.to_vec()))
+            .await
+            .unwrap();
+        assert!(res.is_success());
+        offset += u32::try_from(chunk.len()).unwrap();
+
+        let session = contract.get_upload(session_id).await.unwrap().unwrap();
+        assert_eq!(session.received_len, offset);
+        assert_eq!(session.total_len, total_len);
+        assert_eq!(&session.uploader, contract.contract.id());
+        assert_eq!(session.id, session_id);
+        assert_eq!(session.release_id.checksum, checksum.to_string());
+    }
+
+    let res = contract.finalize_upload(session_id, true).await.unwrap();
+    let res = Id::try_from(res.into_result().unwrap().json::<String>().unwrap()).unwrap();
+    assert_eq!(res, id);
+    assert!(contract.get_upload(session_id).await.unwrap().is_none());
+    assert_eq!(contract.get_blob(&id).await.unwrap().0, code_data);
+}
+
+#[tokio::test]
+async fn test_chunked_upload_max_len() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    // The longest data which fits into a storage value along with its length.
+    let code_data: Vec<u8> = (0..4 * 1024 * 1024 - 4).map(|i| (i % 251) as u8).collect();
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum.clone());
+    let total_len = u32::try_from(code_data.len()).unwrap();
+
+    let res = contract
+        .begin_upload(version, total_len, &checksum.to_string())
+        .await
+        .unwrap();
+    let session_id = res.json::<u64>().unwrap();
+    let mut offset = 0;
+    for chunk in code_data.chunks(768 * 1024) {
+        let res = contract
+            .append_chunk(session_id, offset, &Base64VecU8(chunk.to_vec()))
+            .await
+            .unwrap();
+        assert!(res.is_success());
+        offset += u32::try_from(chunk.len()).unwrap();
+    }
+
+    //== Finalizing fits into the 300 TGas attached to a single call
+    let res = contract.finalize_upload(session_id, true).await.unwrap();
+    let res = Id::try_from(res.into_result().unwrap().json::<String>().unwrap()).unwrap();
+    assert_eq!(res, id);
+    assert_eq!(contract.get_blob(&id).await.unwrap().0, code_data);
+}
+
+#[tokio::test]
+async fn test_chunked_upload_checksum_mismatch_and_abort() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let wrong_checksum = Checksum(sha256(&[1, 2, 3]));
+    let total_len = u32::try_from(code_data.len()).unwrap();
+
+    //== Data which doesn't fit into a storage value is rejected
+    let res = contract
+        .begin_upload("v1.2.3", 4 * 1024 * 1024 - 3, &wrong_checksum.to_string())
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .begin_upload("v1.2.3", total_len, &wrong_checksum.to_string())
+        .await
+        .unwrap();
+    let session_id = res.json::<u64>().unwrap();
+
+    //== Incomplete uploads can't be finalized
+    let res = contract
+        .append_chunk(session_id, 0, &Base64VecU8(code_data[..3].to_vec()))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract.finalize_upload(session_id, false).await.unwrap();
+    assert!(res.is_failure());
+
+    //== Data beyond the total length is rejected
+    let res = contract
+        .append_chunk(session_id, 3, &Base64VecU8(code_data.clone()))
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    //== Data not matching the expected checksum is rejected
+    let res = contract
+        .append_chunk(session_id, 3, &Base64VecU8(code_data[3..].to_vec()))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract.finalize_upload(session_id, false).await.unwrap();
+    assert!(res.is_failure());
+    assert!(contract.list().await.unwrap().is_empty());
+
+    //== Aborting removes the session
+    let res = contract.abort_upload(session_id).await.unwrap();
+    assert!(res.is_success());
+    assert!(contract.get_upload(session_id).await.unwrap().is_none());
+    let res = contract.abort_upload(session_id).await.unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_push_with_expected_checksum() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum.clone());
+    let code = Base64VecU8(code_data);
+    let deposit = contract.estimate_push_cost(code.0.len()).await.unwrap();
+    let push = |version: &str, code: &Base64VecU8, expected_checksum: String| {
+        contract.push_with(
+            json!({
+                "version": version,
+                "code": code,
+                "latest": false,
+                "options": { "expected_checksum": expected_checksum },
+            }),
+            deposit,
+        )
+    };
+
+    //== A truncated upload doesn't match the expected checksum
+    let truncated = Base64VecU8(code.0[..4].to_vec());
+    let res = push(version, &truncated, checksum.to_string())
+        .await
+        .unwrap();
+    let err = format!("{:?}", res.into_result().unwrap_err());
+    assert!(err.contains("checksum mismatch"));
+
+    //== The Id must match as a whole
+    let res = push("v1.2.4", &code, id.to_string()).await.unwrap();
+    assert!(res.is_failure());
+    assert!(contract.list().await.unwrap().is_empty());
+
+    let res = push(version, &code, checksum.to_string()).await.unwrap();
+    let res = Id::try_from(res.into_result().unwrap().json::<String>().unwrap()).unwrap();
+    assert_eq!(res, id);
+
+    let res = push("v1.2.4", &code, format!("v1.2.4-{checksum}"))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+}
+
+#[tokio::test]
+async fn test_push_duplicates() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+    let code = Base64VecU8(code_data);
+
+    //== Pushing the identical release again is a no-op
+    for _ in 0..2 {
+        let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+        let res = Id::try_from(res.into_result().unwrap().json::<String>().unwrap()).unwrap();
+        assert_eq!(res, id);
+    }
+    assert_eq!(contract.list().await.unwrap().len(), 1);
+
+    //== A released version can't be reused for other data
+    let other_code = Base64VecU8(vec![100, 121, 31, 20, 0, 23, 33]);
+    let res = contract
+        .push("v1.2.3", &other_code, false, 6)
+        .await
+        .unwrap();
+    let err = format!("{:?}", res.into_result().unwrap_err());
+    assert!(err.contains("already released with different data"));
+
+    //== The same data under a new version is rejected only on demand
+    let res = contract.set_unique_checksums(true).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.push("v1.2.4", &code, false, 6).await.unwrap();
+    let err = format!("{:?}", res.into_result().unwrap_err());
+    assert!(err.contains("the data is already released"));
+
+    let res = contract.set_unique_checksums(false).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.push("v1.2.4", &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+
+    //== Yanked releases can't be pushed again
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+    let err = format!("{:?}", res.into_result().unwrap_err());
+    assert!(err.contains("is yanked"));
+    assert_eq!(contract.list().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_push_prerelease_and_build_metadata() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v3.1.0-rc.1+build.5";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum.clone());
+    let code = Base64VecU8(code_data);
+
+    let res = contract.push(version, &code, true, 6).await.unwrap();
+    let res = res.into_result().unwrap().json::<String>().unwrap();
+    assert_eq!(res, format!("{version}-{checksum}"));
+    assert_eq!(Id::try_from(res).unwrap(), id);
+
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.id.version, version);
+    assert_eq!(res.status, Status::Released);
+    assert_eq!(contract.get_blob(&id).await.unwrap(), code);
+}
+
+#[tokio::test]
+async fn test_computed_and_pinned_latest() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let mut ids = vec![];
+    for (i, version) in ["v1.2.4", "v1.3.0-rc.1", "v1.2.3"].into_iter().enumerate() {
+        let code_data = vec![100, 121, 31, 20, 0, 23, u8::try_from(i).unwrap()];
+        let checksum = Checksum(sha256(&code_data));
+        ids.push(Id::new(Version::try_from(version).unwrap(), checksum));
+        let res = contract
+            .push(version, &Base64VecU8(code_data), false, 6)
+            .await
+            .unwrap();
+        assert!(res.is_success());
+    }
+    let latest_id = |id: &Id| CustomId {
+        version: id.version.to_string(),
+        checksum: id.checksum.to_string(),
+    };
+
+    //== The highest version which is not a pre-release is the latest one
+    assert_eq!(contract.get_latest_mode().await.unwrap(), "Computed");
+    assert_eq!(contract.get_latest().await.unwrap(), latest_id(&ids[0]));
+
+    //== Pinning overrides it
+    let res = contract.pin_latest(&ids[2]).await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(contract.get_latest_mode().await.unwrap(), "Pinned");
+    assert_eq!(contract.get_latest().await.unwrap(), latest_id(&ids[2]));
+
+    let res = contract.unpin_latest().await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(contract.get_latest_mode().await.unwrap(), "Computed");
+    assert_eq!(contract.get_latest().await.unwrap(), latest_id(&ids[0]));
+
+    //== Yanked releases are skipped and unpinned
+    let res = contract.pin_latest(&ids[0]).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.pull(&ids[0]).await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(contract.get_latest_mode().await.unwrap(), "Computed");
+    assert_eq!(contract.get_latest().await.unwrap(), latest_id(&ids[2]));
+
+    let res = contract.pin_latest(&ids[0]).await.unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_restore() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum);
+    let code = Base64VecU8(code_data);
+
+    let res = contract.push(version, &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+
+    //== Only yanked releases are restored
+    let res = contract.restore(&id, None, None).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.purge(&id).await.unwrap();
+    assert!(res.is_success());
+
+    //== Purged data must be provided and match the checksum
+    let res = contract.restore(&id, None, None).await.unwrap();
+    assert!(res.is_failure());
+    let wrong_code = Base64VecU8(vec![1, 2, 3]);
+    let res = contract
+        .restore(&id, Some(&wrong_code), None)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    //== The deposit must cover the storage of the provided data
+    let res = contract
+        .call_with("restore", json!({ "id": id.to_string(), "code": code }))
+        .await
+        .unwrap();
+    assert!(format!("{:?}", res.into_result().unwrap_err()).contains("insufficient deposit"));
+
+    let reason = "yanked by mistake";
+    let res = contract
+        .restore(&id, Some(&code), Some(reason))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let log = res.logs()[0].to_string();
+    let res = res.json::<CustomIdStatus>().unwrap();
+    assert_eq!(res.status, Status::Released);
+
+    let event: serde_json::Value =
+        serde_json::from_str(log.strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+    assert_eq!(event["event"], "release_restored");
+    assert_eq!(event["data"][0]["id"], id.to_string());
+    assert_eq!(
+        event["data"][0]["actor"],
+        contract.contract.id().to_string()
+    );
+    assert_eq!(event["data"][0]["reason"], reason);
+
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Released);
+    assert!(contract.yank_list().await.unwrap().is_empty());
+    assert_eq!(contract.get_blob(&id).await.unwrap(), code);
+    assert!(contract.get_tombstone(&id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_purge() {
+    let contract = TestContract::new(None).await.unwrap();
+    let admin = contract.create_account("admin").await.unwrap();
+    let res = contract
+        .grant_role(contract.owner(), admin.id(), Role::Admin)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    let code_data: Vec<u8> = (0..20 * 1024).map(|_| 0xFF).collect();
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum.clone());
+    let code = Base64VecU8(code_data);
+
+    let res = contract.push(version, &code, false, 10).await.unwrap();
+    assert!(res.is_success());
+
+    //== Only yanked releases are purged
+    let res = contract.purge_as(&admin, &id).await.unwrap();
+    assert!(res.is_failure());
+
+    //== Yanked data stays readable
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(contract.get_blob(&id).await.unwrap(), code);
+
+    let balance = admin.view_account().await.unwrap().balance;
+    let res = contract.purge_as(&admin, &id).await.unwrap();
+    let tombstone = res
+        .into_result()
+        .unwrap()
+        .json::<CustomTombstone>()
+        .unwrap();
+    assert_eq!(tombstone.id.checksum, checksum.to_string());
+    assert_eq!(tombstone.size, 20 * 1024);
+    assert_eq!(&tombstone.purged_by, admin.id());
+
+    //== The freed storage is refunded
+    assert!(admin.view_account().await.unwrap().balance > balance);
+
+    let res: CustomIdStatus = contract.get_status(&id).await.unwrap();
+    assert_eq!(res.status, Status::Purged);
+    assert_eq!(
+        contract
+            .get_tombstone(&id)
+            .await
+            .unwrap()
+            .unwrap()
+            .purged_at,
+        tombstone.purged_at
+    );
+    let res = contract
+        .contract
+        .view("get_blob")
+        .args_json(serde_json::json!({ "id": id.to_string() }))
+        .await
+        .unwrap()
+        .json::<Option<Base64VecU8>>()
+        .unwrap();
+    assert!(res.is_none());
+
+    let res = contract.purge_as(&admin, &id).await.unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_push_storage_deposit() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data: Vec<u8> = (0..10 * 1024).map(|_| 0xFF).collect();
+    let code = Base64VecU8(code_data);
+    let cost = contract.estimate_push_cost(code.0.len()).await.unwrap();
+    assert!(cost > near_units::parse_near!("0.1 N"));
+
+    //== The deposit must cover the storage
+    let args = json!({ "version": "v1.2.3", "code": code, "latest": false });
+    let res = contract.push_with(args.clone(), ONE_YOCTO).await.unwrap();
+    assert!(res.is_failure());
+    assert!(format!("{:?}", res.into_result().unwrap_err()).contains("insufficient deposit"));
+
+    //== The excess is refunded
+    let balance = contract.balance().await.unwrap();
+    let deposit = near_units::parse_near!("5 N");
+    let res = contract.push_with(args, deposit).await.unwrap();
+    assert!(res.is_success());
+    let spent = balance - contract.balance().await.unwrap();
+    assert!(spent < cost + near_units::parse_near!("0.1 N"));
+}
+
+#[tokio::test]
+async fn test_paginated_lists() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let mut ids = vec![];
+    for i in 0..5 {
+        let code_data = vec![100, 121, 31, 20, 0, 23, i];
+        let version = format!("v1.2.{i}");
+        ids.push(Id::new(
+            Version::try_from(version.as_str()).unwrap(),
+            Checksum(sha256(&code_data)),
+        ));
+        let res = contract
+            .push(&version, &Base64VecU8(code_data), false, 6)
+            .await
+            .unwrap();
+        assert!(res.is_success());
+    }
+    for id in &ids[..3] {
+        let res = contract.pull(id).await.unwrap();
+        assert!(res.is_success());
+    }
+    let versions = |page: serde_json::Value, key: Option<&str>| -> Vec<String> {
+        page.as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                let id = key.map_or(item, |key| &item[key]);
+                id["version"].as_str().unwrap().to_string()
+            })
+            .collect()
+    };
+
+    assert_eq!(contract.count("count").await.unwrap(), 5);
+    assert_eq!(contract.count("yank_count").await.unwrap(), 3);
+
+    let page = contract.list_page("list", 1, 2, false).await.unwrap();
+    assert_eq!(versions(page, Some("id")), ["v1.2.1", "v1.2.2"]);
+    let page = contract.list_page("list", 0, 2, true).await.unwrap();
+    assert_eq!(versions(page, Some("id")), ["v1.2.4", "v1.2.3"]);
+    let page = contract.list_page("list", 4, 10, false).await.unwrap();
+    assert_eq!(versions(page, Some("id")), ["v1.2.4"]);
+    let page = contract.list_page("list", 10, 10, false).await.unwrap();
+    assert!(versions(page, Some("id")).is_empty());
+
+    let page = contract.list_page("yank_list", 0, 2, true).await.unwrap();
+    assert_eq!(versions(page, None), ["v1.2.2", "v1.2.1"]);
+
+    //== All arguments are optional
+    assert_eq!(contract.list().await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_get_by_version() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum);
+    let code = Base64VecU8(code_data);
+
+    let res = contract.push(version, &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+
+    let res = contract.get_by_version(version).await.unwrap();
+    assert_eq!(res, Some(CustomIdStatus::new(id.clone(), None)));
+    let res = contract.get_blob_by_version(version).await.unwrap();
+    assert_eq!(res.unwrap().0, code.0);
+
+    assert_eq!(contract.get_by_version("v1.2.4").await.unwrap(), None);
+    assert!(contract
+        .get_blob_by_version("v1.2.4")
+        .await
+        .unwrap()
+        .is_none());
+
+    //== The status of yanked releases is kept
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.get_by_version(version).await.unwrap();
+    assert_eq!(res, Some(CustomIdStatus::new(id, Some(Status::Yanked))));
+}
+
+#[tokio::test]
+async fn test_get_by_checksum() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let mut ids = vec![];
+    for version in ["v1.2.3", "v1.2.4"] {
+        ids.push(Id::new(
+            Version::try_from(version).unwrap(),
+            checksum.clone(),
+        ));
+        let res = contract.push(version, &code, false, 6).await.unwrap();
+        assert!(res.is_success());
+    }
+    let res = contract.pull(&ids[0]).await.unwrap();
+    assert!(res.is_success());
+
+    let expected = vec![
+        CustomIdStatus::new(ids[0].clone(), Some(Status::Yanked)),
+        CustomIdStatus::new(ids[1].clone(), None),
+    ];
+    let res = contract
+        .get_by_checksum(&checksum.to_string())
+        .await
+        .unwrap();
+    assert_eq!(res, expected);
+
+    //== The deployed code hash is base58 encoded
+    let account = contract.create_account("deployed").await.unwrap();
+    let res = account.deploy(&code.0).await.unwrap();
+    assert!(res.is_success());
+    let code_hash = account.view_account().await.unwrap().code_hash;
+    let res = contract
+        .get_by_checksum(&code_hash.to_string())
+        .await
+        .unwrap();
+    assert_eq!(res, expected);
+
+    let other = Checksum(sha256(&[1, 2, 3]));
+    let res = contract.get_by_checksum(&other.to_string()).await.unwrap();
+    assert!(res.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_matching() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let mut ids = vec![];
+    for (i, version) in ["v2.0.0", "v1.5.0", "v1.4.2", "v1.6.0"]
+        .into_iter()
+        .enumerate()
+    {
+        let code_data = vec![100, 121, 31, 20, 0, 23, u8::try_from(i).unwrap()];
+        ids.push(Id::new(
+            Version::try_from(version).unwrap(),
+            Checksum(sha256(&code_data)),
+        ));
+        let res = contract
+            .push(version, &Base64VecU8(code_data), false, 6)
+            .await
+            .unwrap();
+        assert!(res.is_success());
+    }
+    let res = contract.pull(&ids[1]).await.unwrap();
+    assert!(res.is_success());
+
+    let res = contract
+        .list_matching(">=1.4, <1.6", None, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        [
+            CustomIdStatus::new(ids[2].clone(), None),
+            CustomIdStatus::new(ids[1].clone(), Some(Status::Yanked)),
+        ]
+    );
+
+    let res = contract
+        .list_matching("^1", Some(Status::Released), true)
+        .await
+        .unwrap();
+    assert_eq!(res, [CustomIdStatus::new(ids[3].clone(), None)]);
+
+    let res = contract
+        .list_matching("not a requirement", None, false)
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_channels() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let mut ids = vec![];
+    for (i, version) in ["v1.0.0", "v1.1.0", "v1.2.0-beta.1"]
+        .into_iter()
+        .enumerate()
+    {
+        let code_data = vec![100, 121, 31, 20, 0, 23, u8::try_from(i).unwrap()];
+        ids.push(Id::new(
+            Version::try_from(version).unwrap(),
+            Checksum(sha256(&code_data)),
+        ));
+        let channel = if i < 2 { "stable" } else { "beta" };
+        let deposit = contract.estimate_push_cost(code_data.len()).await.unwrap();
+        let args = json!({
+            "version": version,
+            "code": Base64VecU8(code_data),
+            "latest": false,
+            "options": { "channel": channel },
+        });
+        let res = contract.push_with(args, deposit).await.unwrap();
+        assert!(res.is_success());
+    }
+    let custom_id = |id: &Id| CustomId {
+        version: id.version.to_string(),
+        checksum: id.checksum.to_string(),
+    };
+
+    assert_eq!(
+        contract.latest_in("stable").await.unwrap(),
+        Some(custom_id(&ids[1]))
+    );
+    assert_eq!(
+        contract.latest_in("beta").await.unwrap(),
+        Some(custom_id(&ids[2]))
+    );
+    assert_eq!(contract.latest_in("nightly").await.unwrap(), None);
+
+    //== Promotion to a custom channel
+    let res = contract.promote(&ids[0], "testnet").await.unwrap();
+    assert!(res.is_success());
+    let res = contract.promote(&ids[0], "Testnet").await.unwrap();
+    assert!(res.is_failure());
+    let mut channels = contract.list_channels().await.unwrap();
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    let names: Vec<&str> = channels.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["beta", "stable", "testnet"]);
+    assert_eq!(channels[2].latest, Some(custom_id(&ids[0])));
+
+    //== Yanking the latest release falls back to the previous one
+    let res = contract.pull(&ids[1]).await.unwrap();
+    assert!(res.is_success());
+    assert_eq!(
+        contract.latest_in("stable").await.unwrap(),
+        Some(custom_id(&ids[0]))
+    );
+    let res = contract.promote(&ids[1], "stable").await.unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_packages() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let version = "v1.2.3";
+
+    //== The same version may be pushed into each package
+    let res = contract.push(version, &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+    let deposit = contract.estimate_push_cost(code.0.len()).await.unwrap();
+    let push = |package: &str, latest: bool| {
+        contract.push_with(
+            json!({
+                "version": version,
+                "code": code,
+                "latest": latest,
+                "options": { "package": package },
+            }),
+            deposit,
+        )
+    };
+    let mut ids = vec![];
+    for package in ["engine", "eth-connector"] {
+        let res = push(package, true).await.unwrap();
+        assert!(res.is_success());
+        let id = res.into_result().unwrap().json::<String>().unwrap();
+        assert_eq!(id, format!("{package}/{version}-{checksum}"));
+        ids.push(Id::try_from(id).unwrap());
+    }
+    let res = push("Engine", false).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .view_with("list_packages", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(["engine", "eth-connector"]));
+    let res = contract
+        .view_with("count", json!({ "package": "engine" }))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(1));
+    assert_eq!(contract.list().await.unwrap().len(), 1);
+
+    let expected_id = json!({
+        "package": "engine",
+        "version": version,
+        "checksum": checksum.to_string(),
+    });
+    let res = contract
+        .view_with("latest", json!({ "package": "engine" }))
+        .await
+        .unwrap();
+    assert_eq!(res, expected_id);
+    let res = contract
+        .view_with("get_status", json!({ "id": ids[0].to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(res, json!({ "id": expected_id, "status": "Released" }));
+
+    //== Yanking in a package leaves other packages untouched
+    let res = contract.pull(&ids[0]).await.unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("latest", json!({ "package": "engine" }))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(null));
+    let res = contract
+        .view_with("latest", json!({ "package": "eth-connector" }))
+        .await
+        .unwrap();
+    assert_eq!(res["package"], json!("eth-connector"));
+    assert_eq!(contract.get_latest().await.unwrap().version, version);
+    let res = contract
+        .view_with("get_blob", json!({ "id": ids[1].to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(code));
+
+    //== A package created by setting its options must be paid for
+    let args = json!({ "unique_checksums": true, "package": "bridge" });
+    let res = contract
+        .call_with("set_unique_checksums", args.clone())
+        .await
+        .unwrap();
+    assert!(format!("{:?}", res.into_result().unwrap_err()).contains("insufficient deposit"));
+    let deposit = contract.proposal_deposit().await.unwrap();
+    let res = contract
+        .call_with_deposit("set_unique_checksums", args, deposit)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("get_unique_checksums", json!({ "package": "bridge" }))
+        .await
+        .unwrap();
+    assert_eq!(res, json!(true));
+}
+
+#[tokio::test]
+async fn test_release_info() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let version = "v1.2.3";
+    let id = Id::new(Version::try_from(version).unwrap(), checksum);
+    let metadata = json!({
+        "git_commit": "0f3a1c2",
+        "source_url": "https://github.com/aurora-is-near/aurora-engine",
+        "toolchain": "1.72.0",
+        "build_command": "cargo make build",
+        "release_notes": "Initial release",
+        "labels": ["audited"],
+    });
+
+    // The estimate doesn't cover the metadata, the storage of which is
+    // bounded by its JSON length.
+    let deposit = contract
+        .estimate_push_cost(code.0.len() + metadata.to_string().len())
+        .await
+        .unwrap();
+    let args = json!({
+        "version": version,
+        "code": code,
+        "latest": false,
+        "options": { "metadata": metadata },
+    });
+    let res = contract.push_with(args, deposit).await.unwrap();
+    assert!(res.is_success());
+    let info = contract
+        .view_with("get_release_info", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(info["status"], json!("Released"));
+    assert_eq!(info["metadata"], metadata);
+    assert_eq!(info["pushed"]["size"], json!(7));
+    assert_eq!(info["pushed"]["pushed_by"], json!(contract.owner().id()));
+    assert!(info["pushed"]["block_height"].is_string());
+    assert!(info["pushed"]["timestamp"].is_string());
+    assert!(info.get("code").is_none());
+
+    //== Too long fields are rejected
+    let args = json!({
+        "version": "v1.2.4",
+        "code": code,
+        "latest": false,
+        "options": { "metadata": { "toolchain": "x".repeat(257) } },
+    });
+    let res = contract.push_with(args, deposit).await.unwrap();
+    assert!(res.is_failure());
+
+    //== Metadata may be edited by metadata editors only
+    let editor = contract.create_account("editor").await.unwrap();
+    let notes = json!({ "release_notes": "Fixed notes" });
+    let res = contract
+        .set_metadata(&editor, &id, notes.clone())
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .grant_role(contract.owner(), editor.id(), Role::MetadataEditor)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract.set_metadata(&editor, &id, notes).await.unwrap();
+    assert!(res.is_success());
+    let info = contract
+        .view_with("get_release_info", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(info["metadata"]["release_notes"], json!("Fixed notes"));
+    assert_eq!(info["metadata"]["git_commit"], json!(null));
+    assert_eq!(info["pushed"]["size"], json!(7));
+}
+
+#[tokio::test]
+async fn test_events() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+    let events = |logs: Vec<&str>| {
+        logs.into_iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| serde_json::from_str::<serde_json::Value>(json).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged.len(), 2);
+    assert_eq!(
+        events_logged[0]["standard"],
+        json!("aurora-release-repository")
+    );
+    assert_eq!(events_logged[0]["event"], json!("release_pushed"));
+    assert_eq!(events_logged[0]["data"][0]["id"], json!(id.to_string()));
+    assert_eq!(events_logged[0]["data"][0]["size"], json!(7));
+    assert_eq!(events_logged[1]["event"], json!("latest_changed"));
+    assert_eq!(events_logged[1]["data"][0]["id"], json!(id.to_string()));
+
+    //== Pushing the identical release again logs nothing
+    let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+    assert!(events(res.logs()).is_empty());
+
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged[0]["event"], json!("release_yanked"));
+    assert_eq!(events_logged[1]["event"], json!("latest_changed"));
+    assert_eq!(events_logged[1]["data"][0]["id"], json!(null));
+
+    let res = contract.purge(&id).await.unwrap();
+    assert!(res.is_success());
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged[0]["event"], json!("release_purged"));
+    assert_eq!(events_logged[0]["data"][0]["size"], json!(7));
+
+    //== Promoting a release logs once it becomes the latest of the channel
+    let code = Base64VecU8(vec![1, 2, 3]);
+    let other = Id::new(
+        Version::try_from("v1.2.4").unwrap(),
+        Checksum(sha256(&code.0)),
+    );
+    let res = contract.push("v1.2.4", &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+    let res = contract.promote(&other, "stable").await.unwrap();
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged[0]["event"], json!("release_promoted"));
+    assert_eq!(events_logged[0]["data"][0]["channel"], json!("stable"));
+    let res = contract.promote(&other, "stable").await.unwrap();
+    assert!(events(res.logs()).is_empty());
+
+    //== Granting and revoking roles
+    let publisher = contract.create_account("publisher").await.unwrap();
+    let res = contract
+        .grant_role(contract.owner(), publisher.id(), Role::Publisher)
+        .await
+        .unwrap();
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged[0]["event"], json!("role_granted"));
+    assert_eq!(events_logged[0]["data"][0]["role"], json!("Publisher"));
+    let res = contract
+        .revoke_role(contract.owner(), publisher.id(), Role::Publisher)
+        .await
+        .unwrap();
+    assert_eq!(events(res.logs())[0]["event"], json!("role_revoked"));
+
+    //== Transferring the ownership
+    let new_owner = contract.create_account("new-owner").await.unwrap();
+    let res = contract
+        .propose_owner(contract.owner(), new_owner.id())
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract.accept_ownership(&new_owner).await.unwrap();
+    assert!(res.is_success());
+    let events_logged = events(res.logs());
+    assert_eq!(events_logged[0]["event"], json!("owner_changed"));
+    assert_eq!(
+        events_logged[0]["data"][0]["new_owner"],
+        json!(new_owner.id())
+    );
+}
+
+#[tokio::test]
+async fn test_deploy_to() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+    let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+    assert!(res.is_success());
+
+    // Another instance of the repository serves as the target, its view
+    // methods ignore the passed release data.
+    let target = contract.create_account("target").await.unwrap();
+    let wasm =
+        std::fs::read("target/wasm32-unknown-unknown/release/aurora_release_repository.wasm")
+            .unwrap();
+    let res = target.deploy(&wasm).await.unwrap();
+    assert!(res.is_success());
+    let res = target
+        .call(target.id(), "new")
+        .args_json(json!({ "owner_id": target.id() }))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    let deployer = contract.create_account("deployer").await.unwrap();
+    let res = contract
+        .deploy_to(&deployer, target.id(), &id, "get_owner")
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .grant_role(contract.owner(), deployer.id(), Role::Deployer)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    //== The deposit must cover the storage of the record
+    let res = contract
+        .deploy_to_with_deposit(&deployer, target.id(), &id, "get_owner", 0)
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .deploy_to(&deployer, target.id(), &id, "get_owner")
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.contains("\"release_deployed\"") && log.contains("\"success\":true")));
+    assert!(res.json::<bool>().unwrap());
+
+    //== A failed call to the target is logged as well
+    let res = contract
+        .deploy_to(&deployer, target.id(), &id, "unknown_method")
+        .await
+        .unwrap();
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.contains("\"release_deployed\"") && log.contains("\"success\":false")));
+    assert!(!res.json::<bool>().unwrap());
+
+    //== The deposit passed to a failed call is refunded to the deployer
+    let balance = deployer.view_account().await.unwrap().balance;
+    let res = contract
+        .deploy_to_with_deposit(
+            &deployer,
+            target.id(),
+            &id,
+            "unknown_method",
+            near_units::parse_near!("1 N"),
+        )
+        .await
+        .unwrap();
+    assert!(!res.json::<bool>().unwrap());
+    let spent = balance - deployer.view_account().await.unwrap().balance;
+    assert!(spent < near_units::parse_near!("0.1 N"));
+
+    //== Deployments are recorded in the registry
+    let current = contract
+        .view_with("current_release_of", json!({ "account_id": target.id() }))
+        .await
+        .unwrap();
+    assert_eq!(current["id"]["version"], json!("v1.2.3"));
+    assert_eq!(current["deployer"], json!(deployer.id()));
+    assert_eq!(current["outcome"], json!("Succeeded"));
+    let deployments = contract
+        .view_with("deployments_of", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    let outcomes: Vec<_> = deployments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|deployment| deployment["outcome"].clone())
+        .collect();
+    assert_eq!(
+        outcomes,
+        [json!("Succeeded"), json!("Failed"), json!("Failed")]
+    );
+
+    //== Deployments made elsewhere may be recorded explicitly
+    let other: AccountId = "other.test.near".parse().unwrap();
+    let res = contract
+        .record_deployment(&deployer, &other, &id)
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let current = contract
+        .view_with("current_release_of", json!({ "account_id": other }))
+        .await
+        .unwrap();
+    assert_eq!(current["outcome"], json!("Recorded"));
+    let deployments = contract
+        .view_with("deployments_of", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(deployments.as_array().unwrap().len(), 4);
+
+    //== Yanked releases can't be deployed
+    let res = contract.pull(&id).await.unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .deploy_to(&deployer, target.id(), &id, "get_owner")
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+}
+
+#[tokio::test]
+async fn test_upgrade_self() {
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+    let res = contract.push("v1.2.3", &code, true, 6).await.unwrap();
+    assert!(res.is_success());
+
+    let wasm = Base64VecU8(
+        std::fs::read("target/wasm32-unknown-unknown/release/aurora_release_repository.wasm")
+            .unwrap(),
+    );
+    let alice = contract.create_account("alice").await.unwrap();
+    let res = alice
+        .call(contract.contract.id(), "upgrade_self")
+        .args_json(json!({ "code": wasm }))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .contract
+        .call("upgrade_self")
+        .args_json(json!({ "code": wasm }))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success());
+
+    //== The state is kept after the migration
+    assert_eq!(contract.get_latest().await.unwrap().version, "v1.2.3");
+    let res = contract.get_status(&id).await.unwrap();
+    assert_eq!(res, CustomIdStatus::new(id, None));
+}
+
+#[tokio::test]
+async fn test_signatures() {
+    use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey};
+
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+
+    let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    let public_key = format!(
+        "ed25519:{}",
+        near_sdk::bs58::encode(public.to_bytes()).into_string()
+    );
+    let sign = |id: &Id| {
+        let signature = ExpandedSecretKey::from(&secret).sign(id.to_string().as_bytes(), &public);
+        json!([{
+            "public_key": public_key,
+            "signature": Base64VecU8(signature.to_bytes().to_vec()),
+        }])
+    };
+    let deposit = contract
+        .estimate_signed_push_cost(code.0.len(), 1)
+        .await
+        .unwrap();
+    let push = |signatures: serde_json::Value| {
+        contract.push_with(
+            json!({
+                "version": "v1.2.3",
+                "code": code,
+                "latest": false,
+                "options": { "signatures": signatures },
+            }),
+            deposit,
+        )
+    };
+
+    //== Signatures of unregistered signers are rejected
+    let res = push(sign(&id)).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .call_with("add_signer", json!({ "public_key": public_key }))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .call_with("set_min_signatures", json!({ "min_signatures": 1 }))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract.view_with("list_signers", json!({})).await.unwrap();
+    assert_eq!(res, json!([public_key]));
+
+    //== The required signatures can't outnumber the registered signers
+    let res = contract
+        .call_with("set_min_signatures", json!({ "min_signatures": 2 }))
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let res = contract
+        .call_with("remove_signer", json!({ "public_key": public_key }))
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+
+    //== Unsigned releases and signatures over other releases are rejected
+    let res = contract.push("v1.2.3", &code, false, 6).await.unwrap();
+    assert!(res.is_failure());
+    let other = Id::new(Version::try_from("v1.2.4").unwrap(), id.checksum.clone());
+    let res = push(sign(&other)).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = push(sign(&id)).await.unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("get_status", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(res["status"], json!("Released"));
+    assert_eq!(res["signed_by"], json!([public_key]));
+}
+
+#[tokio::test]
+async fn test_eth_signatures() {
+    use near_crypto::{KeyType, SecretKey};
+    use near_sdk::env::keccak256;
+
+    let contract = TestContract::new(None).await.unwrap();
+
+    let code_data = vec![100, 121, 31, 20, 0, 23, 32];
+    let checksum = Checksum(sha256(&code_data));
+    let code = Base64VecU8(code_data);
+    let id = Id::new(Version::try_from("v1.2.3").unwrap(), checksum);
+
+    let secret = SecretKey::from_seed(KeyType::SECP256K1, "council");
+    let address = format!(
+        "0x{}",
+        hex::encode(&keccak256(secret.public_key().key_data())[12..])
+    );
+    let sign = |id: &Id| {
+        let message = id.to_string();
+        let prefixed = format!("\x19Ethereum Signed Message:\n{}{message}", message.len());
+        let signature = secret.sign(&keccak256(prefixed.as_bytes())).to_string();
+        let encoded = signature.strip_prefix("secp256k1:").unwrap();
+        let mut bytes = near_sdk::bs58::decode(encoded).into_vec().unwrap();
+        bytes[64] += 27;
+        json!([format!("0x{}", hex::encode(bytes))])
+    };
+    let deposit = contract
+        .estimate_signed_push_cost(code.0.len(), 1)
+        .await
+        .unwrap();
+    let push = |eth_signatures: serde_json::Value| {
+        contract.push_with(
+            json!({
+                "version": "v1.2.3",
+                "code": code,
+                "latest": false,
+                "options": { "eth_signatures": eth_signatures },
+            }),
+            deposit,
+        )
+    };
+
+    //== Signatures of unregistered signers are rejected
+    let res = push(sign(&id)).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = contract
+        .call_with("add_eth_signer", json!({ "address": address }))
+        .await
+        .unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("list_eth_signers", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(res, json!([address]));
+
+    //== Signatures over other releases recover to other addresses
+    let other = Id::new(Version::try_from("v1.2.4").unwrap(), id.checksum.clone());
+    let res = push(sign(&other)).await.unwrap();
+    assert!(res.is_failure());
+
+    let res = push(sign(&id)).await.unwrap();
+    assert!(res.is_success());
+    let res = contract
+        .view_with("get_status", json!({ "id": id.to_string() }))
+        .await
+        .unwrap();
+    assert_eq!(res["status"], json!("Released"));
+    assert_eq!(res["attested_by"], json!([address]));
+}
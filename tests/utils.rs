@@ -1,11 +1,12 @@
 use aurora_release_repository::id::{Id, Status};
-use near_sdk::json_types::Base64VecU8;
+use aurora_release_repository::roles::Role;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::Deserialize;
 use near_sdk::ONE_YOCTO;
 use serde_json::json;
 use std::str::FromStr;
 use workspaces::result::ExecutionFinalResult;
-use workspaces::{AccountId, Contract};
+use workspaces::{Account, AccountId, Contract};
 
 #[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
 pub struct CustomId {
@@ -31,6 +32,37 @@ impl CustomIdStatus {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomProposal {
+    pub id: u64,
+    pub action: serde_json::Value,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomUploadSession {
+    pub id: u64,
+    pub uploader: AccountId,
+    pub release_id: CustomId,
+    pub total_len: u32,
+    pub received_len: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomTombstone {
+    pub id: CustomId,
+    pub size: u64,
+    pub purged_at: U64,
+    pub purged_by: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+pub struct CustomChannel {
+    pub name: String,
+    pub latest: Option<CustomId>,
+}
+
 pub struct TestContract {
     pub contract: Contract,
 }
@@ -73,6 +105,21 @@ impl TestContract {
         Ok(contract)
     }
 
+    pub fn owner(&self) -> &Account {
+        self.contract.as_account()
+    }
+
+    pub async fn create_account(&self, name: &str) -> anyhow::Result<Account> {
+        Ok(self
+            .contract
+            .as_account()
+            .create_subaccount(name)
+            .initial_balance(near_units::parse_near!("10 N"))
+            .transact()
+            .await?
+            .into_result()?)
+    }
+
     pub async fn push(
         &self,
         version: &str,
@@ -81,29 +128,89 @@ impl TestContract {
         // Terra Gas
         tgas: u64,
     ) -> anyhow::Result<ExecutionFinalResult> {
-        Ok(self
-            .contract
-            .call("push")
+        self.push_as(self.owner(), version, code, latest, tgas)
+            .await
+    }
+
+    pub async fn push_as(
+        &self,
+        caller: &Account,
+        version: &str,
+        code: &Base64VecU8,
+        latest: bool,
+        // Terra Gas
+        tgas: u64,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(code.0.len()).await?;
+        Ok(caller
+            .call(self.contract.id(), "push")
             .args_json(json!({
                 "version": version,
                 "code": code,
                 "latest": latest
             }))
             .gas(tgas * 1_000_000_000_000)
-            .deposit(ONE_YOCTO)
+            .deposit(deposit)
             .transact()
             .await?)
     }
 
-    pub async fn pull(&self, id: &Id) -> anyhow::Result<ExecutionFinalResult> {
-        let res = self
+    /// Calls `push` with the given arguments and deposit.
+    pub async fn push_with(
+        &self,
+        args: serde_json::Value,
+        deposit: u128,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
             .contract
-            .call("pull")
+            .call("push")
+            .args_json(args)
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn balance(&self) -> anyhow::Result<u128> {
+        Ok(self.owner().view_account().await?.balance)
+    }
+
+    pub async fn estimate_push_cost(&self, code_len: usize) -> anyhow::Result<u128> {
+        self.estimate_signed_push_cost(code_len, 0).await
+    }
+
+    pub async fn estimate_signed_push_cost(
+        &self,
+        code_len: usize,
+        signatures: u32,
+    ) -> anyhow::Result<u128> {
+        let cost: U128 = self
+            .contract
+            .view("estimate_push_cost")
+            .args_json(json!({ "code_len": code_len, "signatures": signatures }))
+            .await?
+            .json()?;
+        Ok(cost.0)
+    }
+
+    /// Deposit covering the storage of a proposal. The excess is refunded.
+    pub async fn proposal_deposit(&self) -> anyhow::Result<u128> {
+        self.estimate_push_cost(0).await
+    }
+
+    pub async fn pull(&self, id: &Id) -> anyhow::Result<ExecutionFinalResult> {
+        self.pull_as(self.owner(), id).await
+    }
+
+    pub async fn pull_as(&self, caller: &Account, id: &Id) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        let res = caller
+            .call(self.contract.id(), "pull")
             .args_json(json!({
                 "id": id.to_string(),
             }))
             .gas(6_000_000_000_000)
-            .deposit(ONE_YOCTO)
+            .deposit(deposit)
             .transact()
             .await?;
         Ok(res)
@@ -118,6 +225,178 @@ impl TestContract {
             .json()?)
     }
 
+    pub async fn get_by_version(&self, version: &str) -> anyhow::Result<Option<CustomIdStatus>> {
+        Ok(self
+            .contract
+            .view("get_by_version")
+            .args_json(json!({ "version": version }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn get_blob_by_version(&self, version: &str) -> anyhow::Result<Option<Base64VecU8>> {
+        Ok(self
+            .contract
+            .view("get_blob_by_version")
+            .args_json(json!({ "version": version }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn get_by_checksum(&self, checksum: &str) -> anyhow::Result<Vec<CustomIdStatus>> {
+        Ok(self
+            .contract
+            .view("get_by_checksum")
+            .args_json(json!({ "checksum": checksum }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn list_matching(
+        &self,
+        requirement: &str,
+        status: Option<Status>,
+        max_only: bool,
+    ) -> anyhow::Result<Vec<CustomIdStatus>> {
+        Ok(self
+            .contract
+            .view("list_matching")
+            .args_json(json!({
+                "requirement": requirement,
+                "status": status,
+                "max_only": max_only,
+            }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn set_metadata(
+        &self,
+        caller: &Account,
+        id: &Id,
+        metadata: serde_json::Value,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(0).await?;
+        Ok(caller
+            .call(self.contract.id(), "set_metadata")
+            .args_json(json!({ "id": id.to_string(), "metadata": metadata }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn deploy_to(
+        &self,
+        caller: &Account,
+        target: &AccountId,
+        id: &Id,
+        method: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit: U128 = self
+            .contract
+            .view("estimate_deploy_cost")
+            .args_json(json!({ "id": id.to_string() }))
+            .await?
+            .json()?;
+        self.deploy_to_with_deposit(caller, target, id, method, deposit.0)
+            .await
+    }
+
+    pub async fn deploy_to_with_deposit(
+        &self,
+        caller: &Account,
+        target: &AccountId,
+        id: &Id,
+        method: &str,
+        deposit: u128,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(caller
+            .call(self.contract.id(), "deploy_to")
+            .args_json(json!({
+                "target": target,
+                "id": id.to_string(),
+                "method": method,
+            }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn record_deployment(
+        &self,
+        caller: &Account,
+        target: &AccountId,
+        id: &Id,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(0).await?;
+        Ok(caller
+            .call(self.contract.id(), "record_deployment")
+            .args_json(json!({ "target": target, "id": id.to_string() }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn call_with(
+        &self,
+        method: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        self.call_with_deposit(method, args, ONE_YOCTO).await
+    }
+
+    pub async fn call_with_deposit(
+        &self,
+        method: &str,
+        args: serde_json::Value,
+        deposit: u128,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .contract
+            .call(method)
+            .args_json(args)
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn view_with(
+        &self,
+        method: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Ok(self.contract.view(method).args_json(args).await?.json()?)
+    }
+
+    pub async fn promote(&self, id: &Id, channel: &str) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(self
+            .contract
+            .call("promote")
+            .args_json(json!({ "id": id.to_string(), "channel": channel }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn latest_in(&self, channel: &str) -> anyhow::Result<Option<CustomId>> {
+        Ok(self
+            .contract
+            .view("latest_in")
+            .args_json(json!({ "channel": channel }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn list_channels(&self) -> anyhow::Result<Vec<CustomChannel>> {
+        Ok(self.contract.view("list_channels").await?.json()?)
+    }
+
     pub async fn get_latest(&self) -> anyhow::Result<CustomId> {
         Ok(self.contract.view("latest").await.unwrap().json()?)
     }
@@ -138,4 +417,346 @@ impl TestContract {
     pub async fn yank_list(&self) -> anyhow::Result<Vec<CustomId>> {
         Ok(self.contract.view("yank_list").await.unwrap().json()?)
     }
+
+    pub async fn list_page(
+        &self,
+        method: &str,
+        from_index: u64,
+        limit: u64,
+        reverse: bool,
+    ) -> anyhow::Result<serde_json::Value> {
+        Ok(self
+            .contract
+            .view(method)
+            .args_json(json!({
+                "from_index": from_index,
+                "limit": limit,
+                "reverse": reverse,
+            }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn count(&self, method: &str) -> anyhow::Result<u64> {
+        Ok(self.contract.view(method).await?.json()?)
+    }
+
+    pub async fn get_owner(&self) -> anyhow::Result<AccountId> {
+        Ok(self.contract.view("get_owner").await?.json()?)
+    }
+
+    pub async fn get_pending_owner(&self) -> anyhow::Result<Option<AccountId>> {
+        Ok(self.contract.view("get_pending_owner").await?.json()?)
+    }
+
+    pub async fn propose_owner(
+        &self,
+        caller: &Account,
+        new_owner: &AccountId,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(caller
+            .call(self.contract.id(), "propose_owner")
+            .args_json(json!({ "new_owner": new_owner }))
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn accept_ownership(&self, caller: &Account) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(caller
+            .call(self.contract.id(), "accept_ownership")
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn cancel_ownership_transfer(
+        &self,
+        caller: &Account,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(caller
+            .call(self.contract.id(), "cancel_ownership_transfer")
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn grant_role(
+        &self,
+        caller: &Account,
+        account_id: &AccountId,
+        role: Role,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(caller
+            .call(self.contract.id(), "grant_role")
+            .args_json(json!({ "account_id": account_id, "role": role }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn revoke_role(
+        &self,
+        caller: &Account,
+        account_id: &AccountId,
+        role: Role,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(caller
+            .call(self.contract.id(), "revoke_role")
+            .args_json(json!({ "account_id": account_id, "role": role }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn has_role(&self, account_id: &AccountId, role: Role) -> anyhow::Result<bool> {
+        Ok(self
+            .contract
+            .view("has_role")
+            .args_json(json!({ "account_id": account_id, "role": role }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn list_role_members(&self, role: Role) -> anyhow::Result<Vec<AccountId>> {
+        Ok(self
+            .contract
+            .view("list_role_members")
+            .args_json(json!({ "role": role }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn set_approval_policy(
+        &self,
+        approvers: &[&AccountId],
+        threshold: u32,
+        ttl: u64,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(self
+            .contract
+            .call("set_approval_policy")
+            .args_json(json!({
+                "policy": {
+                    "approvers": approvers,
+                    "threshold": threshold,
+                    "ttl": ttl.to_string(),
+                }
+            }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn approve(
+        &self,
+        caller: &Account,
+        proposal_id: u64,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(caller
+            .call(self.contract.id(), "approve")
+            .args_json(json!({ "proposal_id": proposal_id }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn cancel_proposal(
+        &self,
+        caller: &Account,
+        proposal_id: u64,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(caller
+            .call(self.contract.id(), "cancel_proposal")
+            .args_json(json!({ "proposal_id": proposal_id }))
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn list_proposals(&self) -> anyhow::Result<Vec<CustomProposal>> {
+        Ok(self.contract.view("list_proposals").await?.json()?)
+    }
+
+    pub async fn begin_upload(
+        &self,
+        version: &str,
+        total_len: u32,
+        expected_sha256: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(0).await?;
+        Ok(self
+            .contract
+            .call("begin_upload")
+            .args_json(json!({
+                "version": version,
+                "total_len": total_len,
+                "expected_sha256": expected_sha256,
+            }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn append_chunk(
+        &self,
+        session_id: u64,
+        offset: u32,
+        bytes: &Base64VecU8,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(bytes.0.len()).await?;
+        Ok(self
+            .contract
+            .call("append_chunk")
+            .args_json(json!({
+                "session_id": session_id,
+                "offset": offset,
+                "bytes": bytes,
+            }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn finalize_upload(
+        &self,
+        session_id: u64,
+        latest: bool,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.estimate_push_cost(0).await?;
+        Ok(self
+            .contract
+            .call("finalize_upload")
+            .args_json(json!({ "session_id": session_id, "latest": latest }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn abort_upload(&self, session_id: u64) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .contract
+            .call("abort_upload")
+            .args_json(json!({ "session_id": session_id }))
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn get_upload(&self, session_id: u64) -> anyhow::Result<Option<CustomUploadSession>> {
+        Ok(self
+            .contract
+            .view("get_upload")
+            .args_json(json!({ "session_id": session_id }))
+            .await?
+            .json()?)
+    }
+
+    pub async fn set_unique_checksums(
+        &self,
+        unique_checksums: bool,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .contract
+            .call("set_unique_checksums")
+            .args_json(json!({ "unique_checksums": unique_checksums }))
+            .max_gas()
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await?)
+    }
+
+    pub async fn get_latest_mode(&self) -> anyhow::Result<String> {
+        Ok(self.contract.view("get_latest_mode").await?.json()?)
+    }
+
+    pub async fn pin_latest(&self, id: &Id) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(self
+            .contract
+            .call("pin_latest")
+            .args_json(json!({ "id": id.to_string() }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn unpin_latest(&self) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(self
+            .contract
+            .call("unpin_latest")
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn restore(
+        &self,
+        id: &Id,
+        code: Option<&Base64VecU8>,
+        reason: Option<&str>,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self
+            .estimate_push_cost(code.map_or(0, |code| code.0.len()))
+            .await?;
+        Ok(self
+            .contract
+            .call("restore")
+            .args_json(json!({
+                "id": id.to_string(),
+                "code": code,
+                "reason": reason,
+            }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn purge(&self, id: &Id) -> anyhow::Result<ExecutionFinalResult> {
+        self.purge_as(self.owner(), id).await
+    }
+
+    pub async fn purge_as(
+        &self,
+        caller: &Account,
+        id: &Id,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let deposit = self.proposal_deposit().await?;
+        Ok(caller
+            .call(self.contract.id(), "purge")
+            .args_json(json!({ "id": id.to_string() }))
+            .max_gas()
+            .deposit(deposit)
+            .transact()
+            .await?)
+    }
+
+    pub async fn get_tombstone(&self, id: &Id) -> anyhow::Result<Option<CustomTombstone>> {
+        Ok(self
+            .contract
+            .view("get_tombstone")
+            .args_json(json!({ "id": id.to_string() }))
+            .await?
+            .json()?)
+    }
 }